@@ -0,0 +1,87 @@
+//! Allocations and time per record when encoding one name per record.
+//!
+//! Compares the inline storage of [`CologneVec`] with a `CologneVec` forced onto the heap via
+//! [`CologneVec::from_inner`], which is how every `CologneVec` was stored before.
+//!
+//! Run with `cargo bench --bench inline_storage`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use cologne_phonetics::CologneVec;
+
+/// Allocator counting all allocations
+struct CountingAlloc;
+
+/// Number of allocations and reallocations
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const FIRST_NAMES: [&str; 8] = [
+    "Hans", "Jürgen", "Marius", "Christoph", "Anna", "Xaver", "Sophie", "Wilhelmine",
+];
+const LAST_NAMES: [&str; 8] = [
+    "Müller", "Meier", "Schmidt", "Breschnew", "Lüdenscheidt", "Wikipedia", "Mustermann", "Zott",
+];
+const RECORDS: usize = 1_000_000;
+
+/// Encode every name into its own `CologneVec` created by `new` and keep all of them.
+fn run(label: &str, names: &[String], new: impl Fn() -> CologneVec) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let mut records = Vec::with_capacity(names.len());
+    for name in names {
+        let mut vec = new();
+        vec.read_from_utf8(name.as_bytes());
+        records.push(vec);
+    }
+    let elapsed = start.elapsed();
+    // The allocation of the records Vec itself is not counted per record
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before - 1;
+    let inline = records.iter().filter(|r| r.is_inline()).count();
+    black_box(&records);
+    println!(
+        "{:<8} {:>6.3} allocations/record {:>7.1} ns/record {:>5.1}% inline",
+        label,
+        allocations as f64 / names.len() as f64,
+        elapsed.as_nanos() as f64 / names.len() as f64,
+        inline as f64 * 100.0 / names.len() as f64,
+    );
+}
+
+fn main() {
+    let names: Vec<String> = (0..RECORDS)
+        .map(|i| {
+            let first = FIRST_NAMES[i % FIRST_NAMES.len()];
+            let last = LAST_NAMES[(i / FIRST_NAMES.len()) % LAST_NAMES.len()];
+            format!("{} {}", first, last)
+        })
+        .collect();
+
+    for _ in 0..3 {
+        run("inline", &names, CologneVec::new);
+        run("heap", &names, || CologneVec::from_inner(Vec::new()));
+    }
+}
@@ -16,7 +16,7 @@ fn run<R: Read>(r: &mut R) {
     let pre = std::time::Instant::now();
     outbuf.read_from_utf8(&cont);
     eprintln!("Took: {:?}", pre.elapsed());
-    writeln!(stdout, "{:?}", outbuf).unwrap();
+    writeln!(stdout, "{}", outbuf).unwrap();
     stdout.write_all(b"\n").unwrap();
     stdout.flush().unwrap();
 }
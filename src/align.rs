@@ -0,0 +1,185 @@
+//! Alignment of the codes of two inputs to show where they differ.
+
+use core::ops::Range;
+
+use alloc::vec::Vec;
+
+use crate::*;
+
+/// How a pair of aligned codes relates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edit {
+    /// Both inputs have the same code
+    Match,
+    /// The inputs have different codes
+    Substitution,
+    /// The code is only in the right input
+    Insertion,
+    /// The code is only in the left input
+    Deletion,
+}
+
+/// One column of an alignment, see [`align`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AlignedCode {
+    /// How the codes relate
+    pub edit: Edit,
+    /// The code of the left input and its source span, `None` for an [`Edit::Insertion`]
+    pub left: Option<(CologneCode, Range<usize>)>,
+    /// The code of the right input and its source span, `None` for an [`Edit::Deletion`]
+    pub right: Option<(CologneCode, Range<usize>)>,
+}
+
+/// Align the codes of `left` and `right`, see [`SpannedCodes::align`].
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{align, CologneCode, Edit};
+/// let alignment = align("Meier", "Maurer");
+/// let edits: Vec<Edit> = alignment.iter().map(|a| a.edit).collect();
+/// assert_eq!(edits, [Edit::Match, Edit::Match, Edit::Insertion]);
+/// // The extra 7 is the second R
+/// assert_eq!(alignment[2].right, Some((CologneCode::Class7, 5..6)));
+/// ```
+pub fn align(left: &str, right: &str) -> Vec<AlignedCode> {
+    align_with_options(left, right, &EncodeOptions::DEFAULT)
+}
+
+/// [`align`] using the given [`EncodeOptions`] for both inputs.
+pub fn align_with_options(left: &str, right: &str, opts: &EncodeOptions) -> Vec<AlignedCode> {
+    let left = SpannedCodes::encode(left.as_bytes(), opts);
+    let right = SpannedCodes::encode(right.as_bytes(), opts);
+    left.align(&right)
+}
+
+impl SpannedCodes {
+    /// Align the codes of `self` (left) and `other` (right) with the least number of
+    /// substitutions, insertions and deletions.
+    ///
+    /// Every code of both sides is part of exactly one [`AlignedCode`], in order and with its
+    /// source span. Of several alignments with the same number of edits, the one with the
+    /// insertions and deletions as late as possible is chosen.
+    pub fn align(&self, other: &SpannedCodes) -> Vec<AlignedCode> {
+        let left: Vec<_> = self.iter().collect();
+        let right: Vec<_> = other.iter().collect();
+        let width = right.len() + 1;
+
+        // Edit distance between the prefixes left[..i] and right[..j] at i * width + j
+        let mut dist = Vec::with_capacity((left.len() + 1) * width);
+        dist.extend(0..width);
+        for i in 1..=left.len() {
+            dist.push(i);
+            for j in 1..width {
+                let diagonal =
+                    dist[(i - 1) * width + j - 1] + usize::from(left[i - 1].0 != right[j - 1].0);
+                let deletion = dist[(i - 1) * width + j] + 1;
+                let insertion = dist[i * width + j - 1] + 1;
+                dist.push(diagonal.min(deletion).min(insertion));
+            }
+        }
+
+        // Walk back from the end preferring gaps, which places them as late as possible
+        let mut alignment = Vec::with_capacity(left.len().max(right.len()));
+        let (mut i, mut j) = (left.len(), right.len());
+        while i > 0 || j > 0 {
+            let d = dist[i * width + j];
+            if j > 0 && d == dist[i * width + j - 1] + 1 {
+                j -= 1;
+                alignment.push(AlignedCode {
+                    edit: Edit::Insertion,
+                    left: None,
+                    right: Some(right[j].clone()),
+                });
+            } else if i > 0 && d == dist[(i - 1) * width + j] + 1 {
+                i -= 1;
+                alignment.push(AlignedCode {
+                    edit: Edit::Deletion,
+                    left: Some(left[i].clone()),
+                    right: None,
+                });
+            } else {
+                i -= 1;
+                j -= 1;
+                let edit = if left[i].0 == right[j].0 {
+                    Edit::Match
+                } else {
+                    Edit::Substitution
+                };
+                alignment.push(AlignedCode {
+                    edit,
+                    left: Some(left[i].clone()),
+                    right: Some(right[j].clone()),
+                });
+            }
+        }
+        alignment.reverse();
+        alignment
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn alignments() {
+        let edits = |left: &str, right: &str| -> Vec<Edit> {
+            align(left, right).iter().map(|a| a.edit).collect()
+        };
+        assert_eq!(edits("", ""), []);
+        assert_eq!(edits("", "Mayr"), [Edit::Insertion, Edit::Insertion]);
+        assert_eq!(edits("Mayr", ""), [Edit::Deletion, Edit::Deletion]);
+        assert_eq!(edits("Maier", "Mayr"), [Edit::Match, Edit::Match]);
+        assert_eq!(edits("Müller", "Miller"), [Edit::Match; 3]);
+        assert_eq!(
+            edits("Schmidt", "Schmitz"),
+            [Edit::Match, Edit::Match, Edit::Substitution]
+        );
+        assert_eq!(
+            edits("Meier Hans", "Hans"),
+            [[Edit::Deletion; 3], [Edit::Match; 3]].concat()
+        );
+
+        let alignment = align("Schmidt", "Schmitz");
+        assert_eq!(alignment[2].left, Some((CologneCode::Class2, 5..7)));
+        assert_eq!(alignment[2].right, Some((CologneCode::Class8, 5..7)));
+    }
+
+    #[test]
+    fn covers_both_sides() {
+        let inputs = [
+            "",
+            "Meier",
+            "Maurer",
+            "Müller-Lüdenscheidt",
+            "Er kam, Er sah, Er siegte",
+            "Xaver Philipp",
+            "Wikipedia",
+        ];
+        for left in inputs {
+            for right in inputs {
+                let opts = EncodeOptions::DEFAULT;
+                let l = SpannedCodes::encode(left.as_bytes(), &opts);
+                let r = SpannedCodes::encode(right.as_bytes(), &opts);
+                let alignment = l.align(&r);
+                let lefts: Vec<_> = alignment.iter().filter_map(|a| a.left.clone()).collect();
+                let rights: Vec<_> = alignment.iter().filter_map(|a| a.right.clone()).collect();
+                assert!(lefts.into_iter().eq(l.iter()));
+                assert!(rights.into_iter().eq(r.iter()));
+                for a in &alignment {
+                    let edit = match (&a.left, &a.right) {
+                        (Some(l), Some(r)) if l.0 == r.0 => Edit::Match,
+                        (Some(_), Some(_)) => Edit::Substitution,
+                        (None, Some(_)) => Edit::Insertion,
+                        (Some(_), None) => Edit::Deletion,
+                        (None, None) => unreachable!("empty alignment column"),
+                    };
+                    assert_eq!(a.edit, edit);
+                }
+                if left == right {
+                    assert!(alignment.iter().all(|a| a.edit == Edit::Match));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,507 @@
+//! Fixed capacity storage for [`CologneCode`]s without heap allocation.
+
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    sink::{Push, PushRules},
+    *,
+};
+
+/// What a [`CologneArray`] does when the encoded codes don't fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OverflowPolicy {
+    /// Keep the codes that fit, the stored codes are a prefix of the complete encoding.
+    Truncate,
+    /// Keep the codes that fit like [`Truncate`](Self::Truncate), but report the overflow with
+    /// a [`CapacityError`].
+    #[default]
+    Error,
+}
+
+/// Error returned when the codes don't fit into a [`CologneArray`] with
+/// [`OverflowPolicy::Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CapacityError {
+    /// The capacity of the array in codes
+    pub capacity: usize,
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cologne codes exceed the capacity of {} codes", self.capacity)
+    }
+}
+
+impl core::error::Error for CapacityError {}
+
+/// The fill state of a fixed capacity buffer of packed [`CologneCode`]s.
+#[derive(Clone, Copy)]
+struct PackState {
+    /// Number of stored codes
+    len: usize,
+    /// Wether a code was dropped
+    overflowed: bool,
+    /// A trailing space or zero that didn't fit but may still be removed
+    pending: Option<CologneCode>,
+}
+
+impl PackState {
+    /// State of an empty buffer
+    const EMPTY: Self = Self {
+        len: 0,
+        overflowed: false,
+        pending: None,
+    };
+
+    /// Get the stored codes of `inner`
+    fn slice<'a>(&self, inner: &'a [u8]) -> CologneSlice<'a> {
+        // SAFETY: The first len nibbles of inner are always valid codes
+        unsafe { CologneSlice::from_raw_parts(inner, 0, self.len) }
+    }
+
+    /// Overwrite the code at `idx`, `idx` must be smaller than `self.len`.
+    fn set(inner: &mut [u8], idx: usize, code: CologneCode) {
+        if let Some(b) = inner.get_mut(idx >> 1) {
+            if idx & 0x01 == 0 {
+                *b = (*b & 0x0f) | (code.get() << 4);
+            } else {
+                *b = (*b & 0xf0) | code.get();
+            }
+        }
+    }
+
+    /// Get the [`PushRules`] to continue after the stored codes and the pending code
+    fn rules(&self, inner: &[u8]) -> PushRules {
+        let slice = self.slice(inner);
+        match self.pending {
+            Some(pending) => PushRules::after(slice.last(), Some(pending)),
+            None => PushRules::after_slice(slice),
+        }
+    }
+
+    /// See [`CologneArray::push`]
+    fn push(&mut self, inner: &mut [u8], code: CologneCode) {
+        if self.overflowed {
+            return;
+        }
+        // A trailing space or zero which doesn't fit may still be removed by the next code or by
+        // finish, anything else that doesn't fit overflows
+        let removable = matches!(code, CologneCode::Space | CologneCode::Class0);
+        match self.rules(inner).push(code) {
+            Push::LeadingBreak | Push::Duplicate => (),
+            Push::ReplaceZero if removable && self.pending.is_some() => self.pending = Some(code),
+            Push::ReplaceZero | Push::Append if self.pending.is_some() => {
+                self.pending = None;
+                self.overflowed = true;
+            }
+            Push::ReplaceZero => Self::set(inner, self.len - 1, code),
+            Push::Append if removable && self.len == inner.len() * 2 => self.pending = Some(code),
+            Push::Append => self.push_raw(inner, code),
+        }
+    }
+
+    /// See [`CologneArray::push_raw`]
+    fn push_raw(&mut self, inner: &mut [u8], code: CologneCode) {
+        if self.len < inner.len() * 2 {
+            self.len += 1;
+            Self::set(inner, self.len - 1, code);
+        } else {
+            self.overflowed = true;
+        }
+    }
+
+    /// See [`CologneArray::pop`]
+    fn pop(&mut self, inner: &mut [u8]) -> Option<CologneCode> {
+        let last = self.slice(inner).last()?;
+        Self::set(inner, self.len - 1, CologneCode::Class0);
+        self.len -= 1;
+        Some(last)
+    }
+
+    /// See [`CologneArray::finish`]
+    fn finish(&mut self, inner: &mut [u8]) {
+        let remove = self.rules(inner).finish().is_some();
+        if self.pending.take().is_some() {
+            // A pending code which is not removed doesn't fit
+            self.overflowed |= !remove;
+        } else if remove {
+            self.pop(inner);
+        }
+    }
+}
+
+/// Fixed capacity storage for [`CologneCode`]s on the stack.
+///
+/// Uses the same packing as [`CologneVec`], `N` is the number of *bytes*, so a
+/// `CologneArray<N>` holds up to `2 * N` codes. What happens if more codes are pushed is
+/// decided by its [`OverflowPolicy`].
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{CologneArray, OverflowPolicy, CapacityError};
+/// let mut buf = CologneArray::<2>::new();
+/// assert_eq!(buf.read_from_utf8(b"Wikipedia"), Ok(()));
+/// assert_eq!(buf.to_string(), "3412");
+///
+/// buf.clear();
+/// assert_eq!(buf.read_from_utf8(b"Breschnew"), Err(CapacityError { capacity: 4 }));
+///
+/// let mut buf = CologneArray::<2>::with_policy(OverflowPolicy::Truncate);
+/// assert_eq!(buf.read_from_utf8(b"Breschnew"), Ok(()));
+/// assert_eq!(buf.to_string(), "1786");
+/// ```
+#[derive(Clone, Copy)]
+pub struct CologneArray<const N: usize> {
+    /// The fill state of `inner`
+    state: PackState,
+    /// What to do if the codes don't fit
+    policy: OverflowPolicy,
+    /// The packed codes, all nibbles after the stored codes are `0`
+    inner: [u8; N],
+}
+
+impl<const N: usize> CologneArray<N> {
+    /// Create a new empty `CologneArray` with [`OverflowPolicy::Error`].
+    pub const fn new() -> Self {
+        Self::with_policy(OverflowPolicy::Error)
+    }
+
+    /// Create a new empty `CologneArray` with the given [`OverflowPolicy`].
+    pub const fn with_policy(policy: OverflowPolicy) -> Self {
+        Self {
+            state: PackState::EMPTY,
+            policy,
+            inner: [0; N],
+        }
+    }
+
+    /// Get the maximum number of codes this array can hold
+    pub const fn capacity(&self) -> usize {
+        N * 2
+    }
+
+    /// Get the number of stored [`CologneCode`]s
+    pub const fn len(&self) -> usize {
+        self.state.len
+    }
+
+    /// Check if no [`CologneCode`]s are stored
+    pub const fn is_empty(&self) -> bool {
+        self.state.len == 0
+    }
+
+    /// Check if codes were dropped because they didn't fit
+    pub const fn is_truncated(&self) -> bool {
+        self.state.overflowed
+    }
+
+    /// Get the [`OverflowPolicy`] of this array
+    pub const fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Get the raw backing storage, the codes are packed like in a [`CologneVec`]
+    pub fn get_raw(&self) -> &[u8] {
+        self.inner.get(..self.state.len.div_ceil(2)).unwrap_or_default()
+    }
+
+    /// Get a borrowed [`CologneSlice`] of all stored [`CologneCode`]s.
+    pub fn as_slice(&self) -> CologneSlice<'_> {
+        self.state.slice(&self.inner)
+    }
+
+    /// Iterate all stored [`CologneCode`]s
+    pub fn iter(&self) -> Iter<'_> {
+        self.as_slice().iter()
+    }
+
+    /// Get the [`CologneCode`] at `index` or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<CologneCode> {
+        self.as_slice().get(index)
+    }
+
+    /// Get the last stored cologne code
+    pub fn last(&self) -> Option<CologneCode> {
+        self.as_slice().last()
+    }
+
+    /// Clear all codes and the overflow state
+    pub fn clear(&mut self) {
+        self.inner = [0; N];
+        self.state = PackState::EMPTY;
+    }
+
+    /// Convert the given raw text bytes into [`CologneCode`]s, see
+    /// [`CologneVec::read_from_utf8`].
+    pub fn read_from_utf8(&mut self, bytes: &[u8]) -> Result<(), CapacityError> {
+        self.read_from_utf8_with_options(bytes, &EncodeOptions::DEFAULT)
+    }
+
+    /// Convert the given raw text bytes into [`CologneCode`]s using the given
+    /// [`EncodeOptions`].
+    ///
+    /// Returns an error if codes were dropped and the policy is [`OverflowPolicy::Error`].
+    pub fn read_from_utf8_with_options(
+        &mut self,
+        bytes: &[u8],
+        opts: &EncodeOptions,
+    ) -> Result<(), CapacityError> {
+        encode_into(bytes, opts, self);
+        self.check()
+    }
+
+    /// Return an error if codes were dropped and the policy is [`OverflowPolicy::Error`].
+    pub fn check(&self) -> Result<(), CapacityError> {
+        if self.state.overflowed && self.policy == OverflowPolicy::Error {
+            Err(CapacityError {
+                capacity: self.capacity(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Push a new [`CologneCode`] according to the rules of how cologne codes have to be
+    /// created, see [`CologneVec::push`]. Once a code didn't fit all further codes are dropped.
+    pub fn push(&mut self, code: CologneCode) {
+        self.state.push(&mut self.inner, code)
+    }
+
+    /// Push to the end without any other checks, records an overflow if the array is full.
+    pub fn push_raw(&mut self, code: CologneCode) {
+        self.state.push_raw(&mut self.inner, code)
+    }
+
+    /// Remove the last code and return it
+    pub fn pop(&mut self) -> Option<CologneCode> {
+        self.state.pop(&mut self.inner)
+    }
+
+    /// Finish this `CologneArray` by applying the rules on the last element, see
+    /// [`CologneVec::finish`].
+    pub fn finish(&mut self) {
+        self.state.finish(&mut self.inner)
+    }
+}
+
+/// Encode the given utf8 bytes into the caller provided `out` buffer without allocating.
+///
+/// The codes are packed like in a [`CologneVec`] and returned as a [`CologneSlice`] of `out`,
+/// all unused nibbles of `out` are set to `0`. If the codes don't fit into `out` a
+/// [`CapacityError`] is returned.
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{encode_packed, EncodeOptions, CapacityError};
+/// let mut out = [0u8; 4];
+/// let codes = encode_packed(b"Wikipedia", &EncodeOptions::DEFAULT, &mut out).unwrap();
+/// assert_eq!(codes.len(), 4);
+/// assert_eq!(out, [0x34, 0x12, 0, 0]);
+///
+/// let res = encode_packed(b"Wikipedia", &EncodeOptions::DEFAULT, &mut out[..1]);
+/// assert_eq!(res, Err(CapacityError { capacity: 2 }));
+/// ```
+pub fn encode_packed<'a>(
+    bytes: &[u8],
+    opts: &EncodeOptions,
+    out: &'a mut [u8],
+) -> Result<CologneSlice<'a>, CapacityError> {
+    out.fill(0);
+    let mut sink = PackedSink {
+        state: PackState::EMPTY,
+        inner: out,
+    };
+    encode_into(bytes, opts, &mut sink);
+    let PackedSink { state, inner } = sink;
+    if state.overflowed {
+        Err(CapacityError {
+            capacity: inner.len() * 2,
+        })
+    } else {
+        Ok(state.slice(inner))
+    }
+}
+
+/// [`CologneSink`] writing into a borrowed byte buffer
+struct PackedSink<'a> {
+    /// The fill state of `inner`
+    state: PackState,
+    /// The packed codes
+    inner: &'a mut [u8],
+}
+
+impl CologneSink for PackedSink<'_> {
+    #[inline(always)]
+    fn push(&mut self, code: CologneCode) {
+        self.state.push(self.inner, code)
+    }
+
+    fn finish(&mut self) {
+        self.state.finish(self.inner)
+    }
+}
+
+impl<const N: usize> CologneSink for CologneArray<N> {
+    #[inline(always)]
+    fn push(&mut self, code: CologneCode) {
+        CologneArray::push(self, code)
+    }
+
+    fn finish(&mut self) {
+        CologneArray::finish(self)
+    }
+}
+
+impl<const N: usize> Default for CologneArray<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, const N: usize> IntoIterator for &'a CologneArray<N> {
+    type Item = CologneCode;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+/// Compares only the codes, not the [`OverflowPolicy`] or overflow state.
+impl<const N: usize, const M: usize> PartialEq<CologneArray<M>> for CologneArray<N> {
+    fn eq(&self, other: &CologneArray<M>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<const N: usize> Eq for CologneArray<N> {}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> PartialEq<CologneVec> for CologneArray<N> {
+    fn eq(&self, other: &CologneVec) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> PartialEq<CologneArray<N>> for CologneVec {
+    fn eq(&self, other: &CologneArray<N>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<const N: usize> Hash for CologneArray<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+
+impl<const N: usize> fmt::Display for CologneArray<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.as_slice(), f)
+    }
+}
+
+impl<const N: usize> fmt::Debug for CologneArray<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.as_slice(), f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Inputs with their expected codes, independent of the `alloc` feature
+    const INPUTS: [(&str, &str); 10] = [
+        ("", ""),
+        ("a", "0"),
+        ("Pat", "12"),
+        ("Wikipedia", "3412"),
+        ("Müller-Lüdenscheidt", "657 52682"),
+        ("Er kam, Er sah, Er siegte", "07 46 07 8 07 842"),
+        ("Anhand von Grundlagen", "0662 36 4762546"),
+        ("aho aho aho", "0 0 0"),
+        ("Hacico", "084"),
+        (
+            "A B C D E F G H I J K L M N O P Q R S T U V W X Y Z",
+            "0 1 8 2 0 3 4 0 0 4 5 6 6 0 1 4 7 8 2 0 3 3 48 0 8",
+        ),
+    ];
+
+    fn expected_codes(expected: &str) -> impl Iterator<Item = CologneCode> + '_ {
+        expected.chars().filter_map(CologneCode::from_char)
+    }
+
+    #[test]
+    fn core_encoding() {
+        for (input, expected) in INPUTS {
+            let mut arr = CologneArray::<32>::new();
+            assert_eq!(arr.read_from_utf8(input.as_bytes()), Ok(()));
+            assert!(arr.iter().eq(expected_codes(expected)), "{:?}", input);
+
+            let mut out = [0xff; 32];
+            let codes = encode_packed(input.as_bytes(), &EncodeOptions::DEFAULT, &mut out);
+            assert_eq!(codes, Ok(arr.as_slice()), "{:?}", input);
+            assert!(out[arr.get_raw().len()..].iter().all(|b| *b == 0));
+
+            let mut out = [0; 1];
+            let res = encode_packed(input.as_bytes(), &EncodeOptions::DEFAULT, &mut out);
+            assert_eq!(res.is_err(), arr.len() > 2);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn same_as_vec() {
+        for opts in crate::tests::option_sets() {
+            for input in crate::tests::INPUTS {
+                let mut vec = CologneVec::new();
+                vec.read_from_utf8_with_options(input.as_bytes(), &opts);
+                let mut arr = CologneArray::<64>::new();
+                assert_eq!(arr.read_from_utf8_with_options(input.as_bytes(), &opts), Ok(()));
+                assert_eq!(arr, vec, "{:?}", input);
+                assert_eq!(arr.get_raw(), vec.get_raw());
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn check_truncation<const N: usize>() {
+        for (input, _) in INPUTS {
+            let mut vec = CologneVec::new();
+            vec.read_from_utf8(input.as_bytes());
+            let mut arr = CologneArray::<N>::with_policy(OverflowPolicy::Truncate);
+            assert_eq!(arr.read_from_utf8(input.as_bytes()), Ok(()));
+            assert_eq!(arr.is_truncated(), vec.len() > arr.capacity(), "{:?}", input);
+            if !arr.is_truncated() {
+                assert_eq!(arr, vec);
+            }
+            // A trailing space or zero of the prefix is removed by finish
+            let prefix = vec.slice(..vec.len().min(arr.capacity()));
+            assert!(prefix.starts_with(arr.as_slice()), "{:?}", input);
+            assert!(prefix.len() - arr.len() <= 1);
+
+            let mut arr = CologneArray::<N>::new();
+            let res = arr.read_from_utf8(input.as_bytes());
+            assert_eq!(res.is_err(), arr.is_truncated());
+            arr.clear();
+            assert_eq!(arr.check(), Ok(()));
+            assert!(arr.is_empty());
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn truncate_is_prefix() {
+        check_truncation::<1>();
+        check_truncation::<2>();
+        check_truncation::<3>();
+        check_truncation::<4>();
+        check_truncation::<5>();
+        check_truncation::<8>();
+    }
+}
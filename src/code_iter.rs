@@ -0,0 +1,186 @@
+//! Iterators over the packed [`CologneCode`]s of a [`CologneVec`].
+
+use core::iter::FusedIterator;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::*;
+
+/// Get the [`CologneCode`] stored at the nibble index `idx` in `bytes`.
+///
+/// # Safety
+/// The nibble at `idx` must be inside of `bytes` and a valid [`CologneCode`].
+#[inline(always)]
+pub(crate) unsafe fn nibble_at(bytes: &[u8], idx: usize) -> CologneCode {
+    let b = *bytes.get_unchecked(idx >> 1);
+    if idx & 0x01 == 0 {
+        nibble_to_cologne(b >> 4)
+    } else {
+        nibble_to_cologne(b & 0x0f)
+    }
+}
+
+/// Range of nibble indices which are not yet yielded, shared by [`Iter`] and [`IntoIter`].
+#[derive(Debug, Clone)]
+struct Cursor {
+    /// Nibble index of the next code yielded from the front
+    front: usize,
+    /// Nibble index one past the next code yielded from the back
+    back: usize,
+}
+
+impl Cursor {
+    /// Yield the next code from the front of `bytes`.
+    ///
+    /// # Safety
+    /// All nibbles in `front..back` must be inside of `bytes` and valid [`CologneCode`]s.
+    #[inline(always)]
+    unsafe fn next(&mut self, bytes: &[u8]) -> Option<CologneCode> {
+        if self.front < self.back {
+            // SAFETY: front is inside of the initialized range
+            let code = nibble_at(bytes, self.front);
+            self.front = self.front.wrapping_add(1);
+            Some(code)
+        } else {
+            None
+        }
+    }
+
+    /// Yield the next code from the back of `bytes`.
+    ///
+    /// # Safety
+    /// All nibbles in `front..back` must be inside of `bytes` and valid [`CologneCode`]s.
+    #[inline(always)]
+    unsafe fn next_back(&mut self, bytes: &[u8]) -> Option<CologneCode> {
+        if self.front < self.back {
+            self.back = self.back.wrapping_sub(1);
+            // SAFETY: back is inside of the initialized range
+            Some(nibble_at(bytes, self.back))
+        } else {
+            None
+        }
+    }
+
+    /// Get the number of codes which are not yet yielded
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.back.wrapping_sub(self.front)
+    }
+}
+
+/// Borrowing iterator over the [`CologneCode`]s of a [`CologneVec`], created by
+/// [`CologneVec::iter`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    /// The packed codes
+    bytes: &'a [u8],
+    /// The nibbles of `bytes` which are not yet yielded
+    cursor: Cursor,
+}
+
+impl<'a> Iter<'a> {
+    /// Create a new iterator over the nibbles `front..back` of `bytes`.
+    ///
+    /// # Safety
+    /// All nibbles in `front..back` must be inside of `bytes` and valid [`CologneCode`]s.
+    pub(crate) unsafe fn new(bytes: &'a [u8], front: usize, back: usize) -> Self {
+        Self {
+            bytes,
+            cursor: Cursor { front, back },
+        }
+    }
+}
+
+impl Iterator for Iter<'_> {
+    type Item = CologneCode;
+
+    #[inline]
+    fn next(&mut self) -> Option<CologneCode> {
+        // SAFETY: The cursor only covers valid codes of bytes
+        unsafe { self.cursor.next(self.bytes) }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.cursor.len();
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<CologneCode> {
+        // SAFETY: The cursor only covers valid codes of bytes
+        unsafe { self.cursor.next_back(self.bytes) }
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+impl FusedIterator for Iter<'_> {}
+
+#[cfg(feature = "alloc")]
+/// Owning iterator over the [`CologneCode`]s of a [`CologneVec`], created by its
+/// [`IntoIterator`] implementation.
+#[derive(Debug, Clone)]
+pub struct IntoIter {
+    /// The packed codes
+    bytes: Vec<u8>,
+    /// The nibbles of `bytes` which are not yet yielded
+    cursor: Cursor,
+}
+
+#[cfg(feature = "alloc")]
+impl Iterator for IntoIter {
+    type Item = CologneCode;
+
+    #[inline]
+    fn next(&mut self) -> Option<CologneCode> {
+        // SAFETY: The cursor only covers valid codes of bytes
+        unsafe { self.cursor.next(&self.bytes) }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.cursor.len();
+        (len, Some(len))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl DoubleEndedIterator for IntoIter {
+    #[inline]
+    fn next_back(&mut self) -> Option<CologneCode> {
+        // SAFETY: The cursor only covers valid codes of bytes
+        unsafe { self.cursor.next_back(&self.bytes) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ExactSizeIterator for IntoIter {}
+#[cfg(feature = "alloc")]
+impl FusedIterator for IntoIter {}
+
+#[cfg(feature = "alloc")]
+impl IntoIterator for CologneVec {
+    type Item = CologneCode;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        let (bytes, len) = self.into_raw();
+        IntoIter {
+            bytes,
+            cursor: Cursor { front: 0, back: len },
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> IntoIterator for &'a CologneVec {
+    type Item = CologneCode;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
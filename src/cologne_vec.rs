@@ -1,19 +1,25 @@
-use core::{hint, ops::ControlFlow};
+use core::ops::ControlFlow;
 use alloc::vec::Vec;
 
-use crate::*;
+use crate::{
+    sink::{Push, PushRules},
+    slice::validate_packed,
+    storage::Storage,
+    *,
+};
 
 /// Optimized data structure to store [`CologneCode`]s.
 ///
 /// As a single [`CologneCode`] only requires 4 bits of storage we store two in a single byte 
-/// to reduce memory usage and improve cache locality.
-#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+/// to reduce memory usage and improve cache locality. Up to 22 codes are stored inline without
+/// any heap allocation, longer codes are moved to the heap.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct CologneVec {
     /// Number of stored cologne_phonetics this should never overflow as 
     /// self.inner.len() < isize::MAX is guaranteed
     len: usize,
     /// The inner buffer of this `CologneVec`
-    inner: Vec<u8>,
+    inner: Storage,
 }
 
 impl CologneVec {
@@ -21,32 +27,87 @@ impl CologneVec {
     pub fn new() -> Self {
         Self {
             len: 0,
-            inner: Vec::new(),
+            inner: Storage::new(),
         }
     }
 
     /// Create a new `CologneVec` with a backing storage that can hald at least `cap` *bytes*.
+    /// Only allocates if `cap` exceeds the inline storage.
     pub fn with_capacity(cap: usize) -> Self {
         Self {
             len: 0,
-            inner: Vec::with_capacity(cap),
+            inner: Storage::with_capacity(cap),
         }
     }
 
     /// Create a new `CologneVec` from the given backing storage, the storage will be cleared.
     pub fn from_inner(mut inner: Vec<u8>) -> Self {
         inner.clear();
-        Self { len: 0, inner }
+        Self {
+            len: 0,
+            inner: Storage::Heap(inner),
+        }
     }
 
     /// Create a new `CologneVec` from the given backing storage and a len.
     ///
-    /// SAFETY:
+    /// # Safety
     /// The inner vector must be initialized for atleast len CologneCodes which all have to be valid.
     pub unsafe fn from_raw(inner: Vec<u8>, len: usize) -> Self {
+        Self {
+            len,
+            inner: Storage::Heap(inner),
+        }
+    }
+
+    /// Create a new `CologneVec` by copying the packed codes in `bytes`, the padding nibble of
+    /// an odd `len` is cleared.
+    ///
+    /// # Safety
+    /// `bytes` must hold exactly `len.div_ceil(2)` bytes with `len` valid codes.
+    pub(crate) unsafe fn from_packed(bytes: &[u8], len: usize) -> Self {
+        let mut inner = Storage::with_capacity(bytes.len());
+        inner.extend_from_slice(bytes);
+        if !Self::byte_bound(len) {
+            if let Some(last) = inner.last_mut() {
+                *last &= 0xf0;
+            }
+        }
         Self { len, inner }
     }
 
+    /// Create a new `CologneVec` from untrusted backing storage and a len, e.g. storage which
+    /// was previously returned by [`into_raw`](Self::into_raw) and persisted.
+    ///
+    /// Every code and the padding nibble are validated, see [`validate`](Self::validate).
+    ///
+    /// # Example
+    /// ```
+    /// # use cologne_phonetics::{CologneVec, InvalidCode};
+    /// let mut buf = CologneVec::new();
+    /// buf.read_from_utf8(b"Breschnew");
+    /// let (inner, len) = buf.clone().into_raw();
+    /// assert_eq!(CologneVec::try_from_raw(inner, len), Ok(buf));
+    /// assert_eq!(
+    ///     CologneVec::try_from_raw(vec![0x1f], 2),
+    ///     Err(InvalidCode::Nibble { index: 1, value: 0xf })
+    /// );
+    /// ```
+    pub fn try_from_raw(inner: Vec<u8>, len: usize) -> Result<Self, InvalidCode> {
+        let me = Self {
+            len,
+            inner: Storage::Heap(inner),
+        };
+        me.validate()?;
+        Ok(me)
+    }
+
+    /// Check that the backing storage holds exactly `len` valid codes and that the padding
+    /// nibble of an odd len is zero.
+    pub fn validate(&self) -> Result<(), InvalidCode> {
+        validate_packed(&self.inner, self.len)
+    }
+
     /// Create a `CologneVec` from raw [`CologneCode`]s
     pub fn from_codes(codes: &[CologneCode]) -> Self {
         let mut me = Self::new();
@@ -57,14 +118,64 @@ impl CologneVec {
         me
     }
 
-    /// Get the raw backign storage out this `CologneVec`
+    /// Append the codes of `other` to the end of this `CologneVec`.
+    ///
+    /// If both `self` and `other` are not empty `separator` is pushed in between. The codes
+    /// themselves are copied verbatim, the encoding rules are not applied again.
+    ///
+    /// With [`CologneCode::Space`] as separator, appending the encoding of `b` to the encoding
+    /// of `a` yields exactly the encoding of `a` and `b` joined by a space, as long as both are
+    /// encoded with the same [`EncodeOptions`]. Without a separator the result generally
+    /// differs from encoding the concatenated text, as rules like deduplication are not
+    /// applied across the seam.
+    ///
+    /// # Example
+    /// ```
+    /// # use cologne_phonetics::{CologneVec, CologneCode};
+    /// let mut key = CologneVec::new();
+    /// key.read_from_utf8(b"Mustermann");
+    /// let mut first = CologneVec::new();
+    /// first.read_from_utf8(b"Max");
+    /// key.append(&first, Some(CologneCode::Space));
+    ///
+    /// let mut joined = CologneVec::new();
+    /// joined.read_from_utf8(b"Mustermann Max");
+    /// assert_eq!(key, joined);
+    /// ```
+    pub fn append(&mut self, other: &CologneVec, separator: Option<CologneCode>) {
+        if let Some(sep) = separator {
+            if !self.is_empty() && !other.is_empty() {
+                self.push_raw(sep);
+            }
+        }
+        self.extend_from_slice(other.as_slice());
+    }
+
+    /// Append all codes of `codes` verbatim, without applying any encoding rules.
+    pub fn extend_from_slice(&mut self, codes: CologneSlice<'_>) {
+        if Self::byte_bound(self.len) {
+            let codes = codes.to_vec();
+            self.inner.extend_from_slice(&codes.inner);
+            self.len = self.len.wrapping_add(codes.len);
+        } else {
+            self.inner.reserve(codes.len() >> 1);
+            for code in codes {
+                self.push_raw(code);
+            }
+        }
+    }
+
+    /// Get the raw backign storage out this `CologneVec`. Allocates if the codes are stored
+    /// inline, use [`get_raw`](Self::get_raw) to borrow the bytes instead.
     pub fn into_inner(self) -> Vec<u8> {
-        self.inner
+        self.inner.into_vec()
     }
     
-    /// Get the backing storage and the current len from this `CologneVec`
+    /// Get the backing storage and the current len from this `CologneVec`. Allocates if the
+    /// codes are stored inline, use [`get_raw`](Self::get_raw) and [`len`](Self::len) to borrow
+    /// them instead.
     pub fn into_raw(self) -> (Vec<u8>, usize) {
-        (self.inner, self.len)
+        (self.inner.into_vec(), self.len)
     }
 
     /// Get the number of stored [`CologneCode`]s
@@ -72,48 +183,51 @@ impl CologneVec {
         self.len
     }
 
-    /// Get the raw backing storage as bytes
+    /// Check if no [`CologneCode`]s are stored
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the raw backing storage as bytes, never allocates unlike
+    /// [`into_raw`](Self::into_raw).
     pub fn get_raw(&self) -> &[u8] {
         &self.inner
     }
 
+    /// Check if the codes are stored inline without a heap allocation. Up to 22 codes are
+    /// stored inline, unless the `CologneVec` was created from a `Vec` or already spilled to
+    /// the heap.
+    pub fn is_inline(&self) -> bool {
+        self.inner.is_inline()
+    }
+
     /// Primary entry point. Convert the given raw text bytes into [`CologneCode`]s.
     ///
     /// This function does not allocate any new storage but might reallocate the internal buffer.
     pub fn read_from_utf8(&mut self, bytes: &[u8]) {
-        // Naive iteration
-        let mut utf8 = false;
-        // All values are interpreted as a normal alphabetic character and this maps to their alphabet
-        // index, most ascii punctuation and whitespace characters are 26 and count as a stop
-        let mut last = [26, 26];
-        // Wether the previous character was uncertain and is not yet written
-        let mut prev_uncertain = false;
-
-        for b in bytes {
-            let b = *b;
-            crate::iter!(b, utf8, last, prev_uncertain, CologneVec::push, self);
-        }
+        self.read_from_utf8_with_options(bytes, &EncodeOptions::DEFAULT)
+    }
 
-        self.finish()
+    /// Convert the given raw text bytes into [`CologneCode`]s using the given [`EncodeOptions`].
+    pub fn read_from_utf8_with_options(&mut self, bytes: &[u8], opts: &EncodeOptions) {
+        encode_into(bytes, opts, self)
     }
 
     /// Push a new [`CologneCode`] to the end of this `CologneVec` according to the rules of how
     /// cologne codes have to be created. This automatically dedups codes next to each other.
+    /// A [`CologneCode::Space`] pushed to an empty `CologneVec` is dropped.
     #[inline(always)]
     pub fn push(&mut self, code: CologneCode) {
-        if !self.last_is(code) {
-            let last = self.last_byte();
-            if self.len >= 2 && (last & 0x0f) == 0 && (last >> 4) != CologneCode::Space.get() {
-                self.replace_last(code);
-            } else {
-                self.push_raw(code);
-            }
+        match self.rules().push(code) {
+            Push::Append => self.push_raw(code),
+            Push::ReplaceZero => self.replace_last(code),
+            Push::LeadingBreak | Push::Duplicate => (),
         }
     }
 
     /// Push to the end of the [`CologneVec`] without any other checks.
     #[inline(always)]
-    fn push_raw(&mut self, code: CologneCode) {
+    pub(crate) fn push_raw(&mut self, code: CologneCode) {
         if Self::byte_bound(self.len) {
             self.inner.push(code.get() << 4)
         } else {
@@ -126,25 +240,10 @@ impl CologneVec {
 
     /// Check if the stored [`CologneCode`]s are currently bound to a byte border.
     #[inline(always)]
-    const fn byte_bound(len: usize) -> bool {
+    pub(crate) const fn byte_bound(len: usize) -> bool {
         len & 0x01 == 0
     }
 
-    /// Check if the last stored [`CologneCode`] is equal to the given [`CologneCode`] `code`
-    #[inline(always)]
-    fn last_is(&self, code: CologneCode) -> bool {
-        let code_hi = code.get() << 4;
-        if let Some(last) = self.inner.last() {
-            if Self::byte_bound(self.len) {
-                last << 4 == code_hi
-            } else {
-                *last == code_hi
-            }
-        } else {
-            false
-        }
-    }
-
     /// Get the last stored cologne code
     pub fn last(&self) -> Option<CologneCode> {
         if Self::byte_bound(self.len) {
@@ -163,7 +262,7 @@ impl CologneVec {
     }
 
     /// Replace the last stored [`CologneCode`] with the given `code`.
-    fn replace_last(&mut self, code: CologneCode) {
+    pub(crate) fn replace_last(&mut self, code: CologneCode) {
         if let Some(last) = self.inner.last_mut() {
             if Self::byte_bound(self.len) {
                 *last &= 0xf0;
@@ -190,27 +289,22 @@ impl CologneVec {
         }
     }
 
+    /// Get the [`PushRules`] to continue after the stored codes
+    #[inline(always)]
+    fn rules(&self) -> PushRules {
+        let last = self.last_byte();
+        // SAFETY: The nibbles of stored codes are valid codes
+        let code = |nibble| unsafe { nibble_to_cologne(nibble) };
+        PushRules::after(
+            (self.len >= 2).then(|| code(last >> 4)),
+            (self.len >= 1).then(|| code(last & 0x0f)),
+        )
+    }
+
     /// Finish this `CologneVec` by applying the rules on the last element.
     pub fn finish(&mut self) {
-        let last_byte = self.last_byte();
-        if let Some(l) = self.inner.last_mut() {
-            if last_byte == CologneCode::Space.get() << 4 | CologneCode::Class0.get() {
-                return;
-            }
-            
-            if Self::byte_bound(self.len) {
-                let nib = *l & 0x0f;
-                if nib == CologneCode::Class0.get() || nib == CologneCode::Space.get() {
-                    self.len = self.len.wrapping_sub(1);
-                    *l &= 0xf0;
-                }
-            } else if !Self::byte_bound(self.len) {
-                let nib = *l >> 4;
-                if nib == CologneCode::Class0.get() || nib == CologneCode::Space.get() {
-                    self.len = self.len.wrapping_sub(1);
-                    self.inner.pop();
-                }
-            }
+        if self.rules().finish().is_some() {
+            self.pop();
         }
     }
 
@@ -239,14 +333,213 @@ impl CologneVec {
                 if f(hi).is_break() {
                     return;
                 }
-                f(lo);
+                let _ = f(lo);
             } else {
                 let hi = unsafe { nibble_to_cologne(*last >> 4) };
-                f(hi);
+                let _ = f(hi);
             }
         }
     }
 
+    /// Iterate all [`CologneCode`]s from front to back.
+    ///
+    /// # Example
+    /// ```
+    /// # use cologne_phonetics::{CologneVec, CologneCode};
+    /// let mut buf = CologneVec::new();
+    /// buf.read_from_utf8(b"Wikipedia");
+    /// assert_eq!(buf.iter().map(CologneCode::as_char).collect::<String>(), "3412");
+    /// assert_eq!(buf.iter().rev().next(), Some(CologneCode::Class2));
+    /// ```
+    pub fn iter(&self) -> Iter<'_> {
+        // SAFETY: The first len nibbles of inner are always valid codes
+        unsafe { Iter::new(&self.inner, 0, self.len) }
+    }
+
+    /// Get a borrowed [`CologneSlice`] of all stored [`CologneCode`]s.
+    pub fn as_slice(&self) -> CologneSlice<'_> {
+        // SAFETY: The first len nibbles of inner are always valid codes
+        unsafe { CologneSlice::from_raw_parts(&self.inner, 0, self.len) }
+    }
+
+    /// Get the [`CologneCode`] at `index` or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<CologneCode> {
+        self.as_slice().get(index)
+    }
+
+    /// Get a [`CologneSlice`] of the given range of code indices, see
+    /// [`CologneSlice::slice`].
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds.
+    pub fn slice(&self, range: impl core::ops::RangeBounds<usize>) -> CologneSlice<'_> {
+        self.as_slice().slice(range)
+    }
+
+    /// Iterate the words of this `CologneVec`, see [`CologneSlice::words`].
+    pub fn words(&self) -> Words<'_> {
+        self.as_slice().words()
+    }
+
+    /// Get the number of words in this `CologneVec`, see [`CologneSlice::words`].
+    pub fn word_count(&self) -> usize {
+        self.as_slice().word_count()
+    }
+
+    /// Get the `n`th word of this `CologneVec`, see [`CologneSlice::words`].
+    pub fn nth_word(&self, n: usize) -> Option<CologneSlice<'_>> {
+        self.as_slice().nth_word(n)
+    }
+
+    /// Overwrite the code at `idx`.
+    ///
+    /// # Safety
+    /// `idx` must be smaller than `self.len`.
+    #[inline(always)]
+    unsafe fn set_unchecked(&mut self, idx: usize, code: CologneCode) {
+        let b = self.inner.get_unchecked_mut(idx >> 1);
+        if Self::byte_bound(idx) {
+            *b = (*b & 0x0f) | (code.get() << 4);
+        } else {
+            *b = (*b & 0xf0) | code.get();
+        }
+    }
+
+    /// Get the code at `idx` without bounds checks.
+    ///
+    /// # Safety
+    /// `idx` must be smaller than `self.len`.
+    #[inline(always)]
+    unsafe fn get_unchecked(&self, idx: usize) -> CologneCode {
+        crate::code_iter::nibble_at(&self.inner, idx)
+    }
+
+    /// Shorten this `CologneVec` to `len` codes, has no effect if `len` is greater or equal to
+    /// the current len.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.inner.truncate(len.wrapping_add(1) >> 1);
+            if !Self::byte_bound(len) {
+                if let Some(last) = self.inner.last_mut() {
+                    *last &= 0xf0;
+                }
+            }
+            self.len = len;
+        }
+    }
+
+    /// Remove the last code and return it, or `None` if this `CologneVec` is empty.
+    pub fn pop(&mut self) -> Option<CologneCode> {
+        let last = self.last()?;
+        self.truncate(self.len.wrapping_sub(1));
+        Some(last)
+    }
+
+    /// Insert `code` at `index`, shifting all codes after it to the right.
+    ///
+    /// # Panics
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, code: CologneCode) {
+        assert!(
+            index <= self.len,
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            self.len
+        );
+        self.push_raw(code);
+        let mut i = self.len.wrapping_sub(1);
+        while i > index {
+            // SAFETY: index < i < len
+            unsafe {
+                let prev = self.get_unchecked(i.wrapping_sub(1));
+                self.set_unchecked(i, prev);
+            }
+            i = i.wrapping_sub(1);
+        }
+        // SAFETY: index < len after the push
+        unsafe { self.set_unchecked(index, code) };
+    }
+
+    /// Remove the code at `index` and return it, shifting all codes after it to the left.
+    ///
+    /// # Panics
+    /// Panics if `index >= len`.
+    pub fn remove(&mut self, index: usize) -> CologneCode {
+        assert!(
+            index < self.len,
+            "removal index (is {}) should be < len (is {})",
+            index,
+            self.len
+        );
+        // SAFETY: index < len
+        let code = unsafe { self.get_unchecked(index) };
+        self.move_tail(index.wrapping_add(1), index);
+        code
+    }
+
+    /// Move the codes `from..len` to `to..` and truncate the vec to the moved codes.
+    /// `to` must be smaller or equal to `from`.
+    fn move_tail(&mut self, from: usize, to: usize) {
+        let mut read = from;
+        let mut write = to;
+        while read < self.len {
+            // SAFETY: write <= read < len
+            unsafe {
+                let code = self.get_unchecked(read);
+                self.set_unchecked(write, code);
+            }
+            read = read.wrapping_add(1);
+            write = write.wrapping_add(1);
+        }
+        self.truncate(write);
+    }
+
+    /// Split this `CologneVec` into two at `at`. Returns the codes `at..len`, `self` keeps the
+    /// codes `0..at`.
+    ///
+    /// # Panics
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> CologneVec {
+        let tail = self.slice(at..).to_vec();
+        self.truncate(at);
+        tail
+    }
+
+    /// Retain only the codes for which `f` returns `true`.
+    pub fn retain(&mut self, mut f: impl FnMut(CologneCode) -> bool) {
+        let mut write = 0usize;
+        for read in 0..self.len {
+            // SAFETY: write <= read < len
+            unsafe {
+                let code = self.get_unchecked(read);
+                if f(code) {
+                    self.set_unchecked(write, code);
+                    write = write.wrapping_add(1);
+                }
+            }
+        }
+        self.truncate(write);
+    }
+
+    /// Remove the codes in `range` and return them as an iterator. Unlike [`Vec::drain`] the
+    /// codes are removed immediately, even if the iterator is not consumed.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds.
+    pub fn drain(&mut self, range: impl core::ops::RangeBounds<usize>) -> IntoIter {
+        let Some((start, end)) = crate::slice::resolve_range(&range, self.len) else {
+            panic!("range out of bounds for CologneVec of len {}", self.len);
+        };
+        let drained = self.slice(start..end).to_vec();
+        self.move_tail(end, start);
+        drained.into_iter()
+    }
+
+    /// Display the codes of this `CologneVec` in the given [`CologneFormat`].
+    pub fn display_with<'s>(&self, format: CologneFormat<'s>) -> FormattedCodes<'_, 's> {
+        format.display(self.as_slice())
+    }
+
     /// Clear this [`CologneVec`]
     pub fn clear(&mut self) {
         self.inner.clear();
@@ -254,6 +547,79 @@ impl CologneVec {
     }
 }
 
+/// Parses the textual form of cologne codes as written by
+/// [`utf8_to_cologne_phonetics_string`], e.g. `"657 52682"`.
+///
+/// The characters `0` to `8` and space are stored verbatim, so parsing the output of the
+/// string encoder yields exactly the same codes as [`CologneVec::read_from_utf8`]. Any other
+/// character is rejected with its byte index.
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{CologneVec, InvalidCode};
+/// let mut buf = CologneVec::new();
+/// buf.read_from_utf8("Müller-Lüdenscheidt".as_bytes());
+/// assert_eq!("657 52682".parse::<CologneVec>(), Ok(buf));
+/// assert_eq!(
+///     "657-52682".parse::<CologneVec>(),
+///     Err(InvalidCode::Char { index: 3, value: '-' })
+/// );
+/// ```
+impl core::str::FromStr for CologneVec {
+    type Err = InvalidCode;
+
+    fn from_str(s: &str) -> Result<Self, InvalidCode> {
+        let mut me = Self::with_capacity(s.len().div_ceil(2));
+        for (index, b) in s.bytes().enumerate() {
+            let code = match b {
+                b'0'..=b'8' => CologneCode::from_nibble(b.wrapping_sub(b'0')),
+                b' ' => Some(CologneCode::Space),
+                _ => None,
+            };
+            match code {
+                Some(code) => me.push_raw(code),
+                None => {
+                    let value = s.get(index..).and_then(|rest| rest.chars().next());
+                    return Err(InvalidCode::Char {
+                        index,
+                        value: value.unwrap_or(char::REPLACEMENT_CHARACTER),
+                    });
+                }
+            }
+        }
+        Ok(me)
+    }
+}
+
+/// Collects the codes verbatim like [`from_codes`](CologneVec::from_codes) without applying
+/// any encoding rules, so `vec.iter().collect::<CologneVec>() == vec`.
+impl FromIterator<CologneCode> for CologneVec {
+    fn from_iter<I: IntoIterator<Item = CologneCode>>(iter: I) -> Self {
+        let mut me = Self::new();
+        me.extend(iter);
+        me
+    }
+}
+
+/// Pushes the codes verbatim without applying any encoding rules, use
+/// [`push`](CologneVec::push) and [`finish`](CologneVec::finish) to apply them.
+impl Extend<CologneCode> for CologneVec {
+    fn extend<I: IntoIterator<Item = CologneCode>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.inner.reserve(iter.size_hint().0 >> 1);
+        for code in iter {
+            self.push_raw(code);
+        }
+    }
+}
+
+/// Writes the canonical form like [`utf8_to_cologne_phonetics_string`], e.g. `"657 52682"`.
+impl core::fmt::Display for CologneVec {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.as_slice(), f)
+    }
+}
+
 impl core::fmt::Debug for CologneVec {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "[")?;
@@ -267,6 +633,12 @@ impl core::fmt::Debug for CologneVec {
 
 #[cfg(test)]
 mod test {
+    use alloc::{
+        format,
+        string::{String, ToString},
+        vec,
+    };
+
     use super::*;
 
     #[test]
@@ -523,4 +895,462 @@ mod test {
         assert_eq!(outbuf_little, resvec);
         assert_eq!(outbuf_little, outbuf);
     }
+
+    #[test]
+    fn hyphen_join() {
+        let opts = EncodeOptions::new().hyphen(PunctuationMode::Join);
+        let mut outbuf = CologneVec::new();
+        outbuf.read_from_utf8_with_options("Müller-Lüdenscheidt".as_bytes(), &opts);
+        assert_eq!(
+            outbuf,
+            CologneVec::from_codes(&[
+                CologneCode::Class6,
+                CologneCode::Class5,
+                CologneCode::Class7,
+                CologneCode::Class5,
+                CologneCode::Class2,
+                CologneCode::Class6,
+                CologneCode::Class8,
+                CologneCode::Class2,
+            ])
+        )
+    }
+
+    #[test]
+    fn apostrophe_join_and_ignore() {
+        // The C after the apostrophe is not at the start of a word
+        let mut joined = CologneVec::new();
+        joined.read_from_utf8_with_options(
+            b"O'Cinneide",
+            &EncodeOptions::new().apostrophe(PunctuationMode::Join),
+        );
+        assert_eq!(
+            joined,
+            CologneVec::from_codes(&[
+                CologneCode::Class0,
+                CologneCode::Class8,
+                CologneCode::Class6,
+                CologneCode::Class2,
+            ])
+        );
+
+        // Joined letters are not neighbours but ignored characters vanish completely
+        let mut joined = CologneVec::new();
+        joined.read_from_utf8_with_options(
+            b"Ed'Sa",
+            &EncodeOptions::new().apostrophe(PunctuationMode::Join),
+        );
+        let mut ignored = CologneVec::new();
+        ignored.read_from_utf8_with_options(
+            b"Ed'Sa",
+            &EncodeOptions::new().apostrophe(PunctuationMode::Ignore),
+        );
+        assert_eq!(
+            joined,
+            CologneVec::from_codes(&[CologneCode::Class0, CologneCode::Class2, CologneCode::Class8])
+        );
+        assert_eq!(
+            ignored,
+            CologneVec::from_codes(&[CologneCode::Class0, CologneCode::Class8])
+        );
+    }
+
+    #[test]
+    fn whitespace_always_breaks() {
+        let opts = EncodeOptions::new()
+            .hyphen(PunctuationMode::Ignore)
+            .apostrophe(PunctuationMode::Ignore)
+            .other(PunctuationMode::Ignore);
+        let mut outbuf = CologneVec::new();
+        outbuf.read_from_utf8_with_options(b"Er, kam-a", &opts);
+        assert_eq!(
+            outbuf,
+            CologneVec::from_codes(&[
+                CologneCode::Class0,
+                CologneCode::Class7,
+                CologneCode::Space,
+                CologneCode::Class4,
+                CologneCode::Class6,
+            ])
+        )
+    }
+
+    #[test]
+    fn split_camel_case() {
+        let opts = EncodeOptions::new().split_camel_case(true);
+        let mut outbuf = CologneVec::new();
+        outbuf.read_from_utf8_with_options(b"MaxMustermann", &opts);
+        let mut spaced = CologneVec::new();
+        spaced.read_from_utf8(b"Max Mustermann");
+        assert_eq!(outbuf, spaced);
+
+        // The C after the transition is at the start of a word
+        outbuf.clear();
+        outbuf.read_from_utf8_with_options(b"mueller_Schmidt SaCharow", &opts);
+        spaced.clear();
+        spaced.read_from_utf8(b"mueller Schmidt Sa Charow");
+        assert_eq!(outbuf, spaced);
+
+        // Uppercase runs and uppercase to lowercase transitions are no word breaks
+        outbuf.clear();
+        outbuf.read_from_utf8_with_options(b"MUELLER McDonald", &opts);
+        spaced.clear();
+        spaced.read_from_utf8(b"MUELLER Mc Donald");
+        assert_eq!(outbuf, spaced);
+    }
+
+    #[test]
+    fn iter() {
+        let codes = [
+            CologneCode::Class0,
+            CologneCode::Class6,
+            CologneCode::Class6,
+            CologneCode::Class2,
+            CologneCode::Space,
+            CologneCode::Class3,
+            CologneCode::Class6,
+        ];
+        let mut outbuf = CologneVec::new();
+        outbuf.read_from_utf8("Anhand von".as_bytes());
+        assert_eq!(outbuf.iter().len(), codes.len());
+        assert!(outbuf.iter().eq(codes));
+        assert!(outbuf.iter().rev().eq(codes.into_iter().rev()));
+        assert!(outbuf.clone().into_iter().eq(codes));
+        assert!(outbuf.clone().into_iter().rev().eq(codes.into_iter().rev()));
+
+        let mut it = outbuf.iter();
+        assert_eq!(it.next(), Some(CologneCode::Class0));
+        assert_eq!(it.next_back(), Some(CologneCode::Class6));
+        assert_eq!(it.len(), codes.len() - 2);
+        let mut collected = Vec::new();
+        for code in &outbuf {
+            collected.push(code);
+        }
+        assert_eq!(collected, codes);
+
+        assert_eq!(CologneVec::new().iter().next(), None);
+        assert_eq!(CologneVec::new().into_iter().next_back(), None);
+    }
+
+    #[test]
+    fn trailing_uncertain() {
+        let encode = |input: &str| {
+            let mut outbuf = CologneVec::new();
+            outbuf.read_from_utf8(input.as_bytes());
+            outbuf.to_string()
+        };
+        assert_eq!(encode("Pat"), "12");
+        assert_eq!(encode("Vogt"), "342");
+        assert_eq!(encode("Ernst"), "07682");
+        assert_eq!(encode("Bac Dop"), "18 21");
+    }
+
+    #[test]
+    fn leading_break() {
+        let encode = |input: &str| {
+            let mut outbuf = CologneVec::new();
+            outbuf.read_from_utf8(input.as_bytes());
+            outbuf.to_string()
+        };
+        assert_eq!(encode(" a"), "0");
+        assert_eq!(encode("- Aro"), "07");
+        assert_eq!(encode(" - h Aro"), "07");
+        assert_eq!(encode("  "), "");
+
+        let mut outbuf = CologneVec::new();
+        outbuf.push(CologneCode::Space);
+        assert!(outbuf.is_empty());
+    }
+
+    #[test]
+    fn single_leading_zero() {
+        use CologneCode::*;
+        let mut outbuf = CologneVec::new();
+        outbuf.read_from_utf8(b"a");
+        assert_eq!(outbuf.to_string(), "0");
+        outbuf.clear();
+        outbuf.read_from_utf8(b"aho");
+        assert_eq!(outbuf.to_string(), "0");
+
+        assert_eq!(CologneVec::from_codes(&[Class0]).len(), 1);
+        assert_eq!(CologneVec::from_codes(&[Class5, Class0]).to_string(), "5");
+        assert_eq!(CologneVec::from_codes(&[Class5, Space, Class0]).to_string(), "5 0");
+    }
+
+    #[test]
+    fn collect_and_extend() {
+        let mut outbuf = CologneVec::new();
+        outbuf.read_from_utf8("Er kam, Er sah, Er siegte".as_bytes());
+        assert_eq!(outbuf.iter().collect::<CologneVec>(), outbuf);
+        let mut extended = CologneVec::new();
+        for word in outbuf.words() {
+            extended.extend(word);
+            extended.extend([CologneCode::Space]);
+        }
+        extended.extend_from_slice(CologneSlice::empty());
+        assert_eq!(extended.slice(..outbuf.len()), outbuf);
+        assert_eq!(extended.last(), Some(CologneCode::Space));
+    }
+
+    #[test]
+    fn append_equals_joined_encoding() {
+        let inputs = [
+            "", "a", "h", "-", "Pat", "Bad", "Xaver", "Chemnitz", "Müller-Lüdenscheidt",
+            "Er kam", "aho", "Breschnew", "Ace", "Sc", "Meier", "O'Brien", "MaxMustermann",
+        ];
+        let all_opts = [
+            EncodeOptions::new(),
+            EncodeOptions::new()
+                .hyphen(PunctuationMode::Join)
+                .apostrophe(PunctuationMode::Ignore)
+                .split_camel_case(true),
+        ];
+        for opts in &all_opts {
+            for a in inputs {
+                for b in inputs {
+                    let mut left = CologneVec::new();
+                    left.read_from_utf8_with_options(a.as_bytes(), opts);
+                    let mut right = CologneVec::new();
+                    right.read_from_utf8_with_options(b.as_bytes(), opts);
+                    left.append(&right, Some(CologneCode::Space));
+
+                    let mut joined = CologneVec::new();
+                    joined.read_from_utf8_with_options(format!("{} {}", a, b).as_bytes(), opts);
+                    assert_eq!(left, joined, "{:?} + {:?}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn append_without_separator() {
+        let mut left = CologneVec::new();
+        left.read_from_utf8(b"Pa");
+        let mut right = CologneVec::new();
+        right.read_from_utf8(b"Pa");
+        let mut appended = left.clone();
+        appended.append(&right, None);
+        assert!(appended.iter().eq(left.iter().chain(right.iter())));
+        appended.append(&CologneVec::new(), Some(CologneCode::Space));
+        assert_eq!(appended.len(), left.len() + right.len());
+    }
+
+    /// Small xorshift generator for randomized tests
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % (n as u64)) as usize
+        }
+
+        fn code(&mut self) -> CologneCode {
+            const CODES: [CologneCode; 10] = [
+                CologneCode::Class0,
+                CologneCode::Class1,
+                CologneCode::Class2,
+                CologneCode::Class3,
+                CologneCode::Class4,
+                CologneCode::Class5,
+                CologneCode::Class6,
+                CologneCode::Class7,
+                CologneCode::Class8,
+                CologneCode::Space,
+            ];
+            CODES[self.below(CODES.len())]
+        }
+    }
+
+    #[test]
+    fn editing_matches_vec_model() {
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+        for _ in 0..200 {
+            let mut model: Vec<CologneCode> = Vec::new();
+            let mut vec = CologneVec::new();
+            for _ in 0..64 {
+                match rng.below(9) {
+                    0 | 1 => {
+                        let code = rng.code();
+                        model.push(code);
+                        vec.extend([code]);
+                    }
+                    2 => {
+                        let idx = rng.below(model.len() + 1);
+                        let code = rng.code();
+                        model.insert(idx, code);
+                        vec.insert(idx, code);
+                    }
+                    3 if !model.is_empty() => {
+                        let idx = rng.below(model.len());
+                        assert_eq!(vec.remove(idx), model.remove(idx));
+                    }
+                    4 => assert_eq!(vec.pop(), model.pop()),
+                    5 => {
+                        let len = rng.below(model.len() + 2);
+                        model.truncate(len);
+                        vec.truncate(len);
+                    }
+                    6 => {
+                        let at = rng.below(model.len() + 1);
+                        let model_tail = model.split_off(at);
+                        let tail = vec.split_off(at);
+                        assert_eq!(tail, model_tail.into_iter().collect::<CologneVec>());
+                    }
+                    7 => {
+                        let removed = rng.code();
+                        model.retain(|c| *c != removed);
+                        vec.retain(|c| c != removed);
+                    }
+                    _ => {
+                        let start = rng.below(model.len() + 1);
+                        let end = start + rng.below(model.len() - start + 1);
+                        let drained: Vec<_> = model.drain(start..end).collect();
+                        assert!(vec.drain(start..end).eq(drained));
+                    }
+                }
+                assert_eq!(vec.len(), model.len());
+                // Also checks that the padding nibble is cleared
+                assert_eq!(vec, model.iter().copied().collect::<CologneVec>());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_out_of_bounds() {
+        let mut vec = CologneVec::from_codes(&[CologneCode::Class1]);
+        vec.insert(2, CologneCode::Class2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_out_of_bounds() {
+        let mut vec = CologneVec::from_codes(&[CologneCode::Class1]);
+        vec.remove(1);
+    }
+
+    #[test]
+    fn try_from_raw() {
+        let mut outbuf = CologneVec::new();
+        outbuf.read_from_utf8("Müller-Lüdenscheidt".as_bytes());
+        assert_eq!(outbuf.validate(), Ok(()));
+        let (inner, len) = outbuf.clone().into_raw();
+        assert_eq!(CologneVec::try_from_raw(inner.clone(), len), Ok(outbuf));
+
+        assert_eq!(
+            CologneVec::try_from_raw(inner.clone(), len + 2),
+            Err(InvalidCode::Length { len: len + 2, bytes: inner.len() })
+        );
+        assert_eq!(
+            CologneVec::try_from_raw(inner.clone(), len - 2),
+            Err(InvalidCode::Length { len: len - 2, bytes: inner.len() })
+        );
+        let mut padded = inner.clone();
+        *padded.last_mut().unwrap() |= 0x01;
+        assert_eq!(
+            CologneVec::try_from_raw(padded, len),
+            Err(InvalidCode::Padding { value: 1 })
+        );
+        for value in [9, 10, 11, 12, 13, 15] {
+            let mut corrupt = inner.clone();
+            corrupt[1] = (corrupt[1] & 0xf0) | value;
+            assert_eq!(
+                CologneVec::try_from_raw(corrupt, len),
+                Err(InvalidCode::Nibble { index: 3, value })
+            );
+            let mut corrupt = inner.clone();
+            corrupt[2] = (corrupt[2] & 0x0f) | (value << 4);
+            assert_eq!(
+                CologneVec::try_from_raw(corrupt, len),
+                Err(InvalidCode::Nibble { index: 4, value })
+            );
+        }
+        assert_eq!(CologneVec::try_from_raw(Vec::new(), 0), Ok(CologneVec::new()));
+    }
+
+    #[test]
+    fn from_str_round_trip() {
+        let inputs = [
+            "",
+            "Wikipedia",
+            "Müller-Lüdenscheidt",
+            "Er kam, Er sah, Er siegte",
+            "Anhand von Grundlagen",
+            "aho aho aho",
+            "A B C D E F G H I J K L M N O P Q R S T U V W X Y Z",
+            "a!\"#$%&'()*+,-./0123456789:;<=>?@[\\]^_`{|}~a`",
+        ];
+        for input in inputs {
+            let mut text = String::new();
+            utf8_to_cologne_phonetics_string(input.as_bytes(), &mut text);
+            let mut outbuf = CologneVec::new();
+            outbuf.read_from_utf8(input.as_bytes());
+            assert_eq!(text.parse::<CologneVec>(), Ok(outbuf.clone()), "{:?}", input);
+            assert_eq!(format!("{:?}", outbuf), format!("[{}]", text));
+        }
+
+        assert_eq!(
+            "12a".parse::<CologneVec>(),
+            Err(InvalidCode::Char { index: 2, value: 'a' })
+        );
+        assert_eq!(
+            "1ä9".parse::<CologneVec>(),
+            Err(InvalidCode::Char { index: 1, value: 'ä' })
+        );
+        assert_eq!(
+            "0 9".parse::<CologneVec>(),
+            Err(InvalidCode::Char { index: 2, value: '9' })
+        );
+    }
+
+    #[test]
+    fn code_from_str() {
+        assert_eq!("7".parse::<CologneCode>(), Ok(CologneCode::Class7));
+        assert_eq!(" ".parse::<CologneCode>(), Ok(CologneCode::Space));
+        assert_eq!("".parse::<CologneCode>(), Err(InvalidCode::Empty));
+        assert_eq!(
+            "77".parse::<CologneCode>(),
+            Err(InvalidCode::Char { index: 1, value: '7' })
+        );
+        assert_eq!(
+            "x".parse::<CologneCode>(),
+            Err(InvalidCode::Char { index: 0, value: 'x' })
+        );
+    }
+
+    #[test]
+    fn inline_storage() {
+        let mut outbuf = CologneVec::new();
+        outbuf.read_from_utf8("Müller-Lüdenscheidt".as_bytes());
+        assert!(outbuf.is_inline());
+        assert_eq!(outbuf.get_raw(), [0x65, 0x7e, 0x52, 0x68, 0x20]);
+        assert_eq!(outbuf.into_raw(), (vec![0x65, 0x7e, 0x52, 0x68, 0x20], 9));
+
+        let long: CologneVec = "0123456 0123456 01234567".parse().unwrap();
+        assert_eq!(long.len(), 24);
+        assert!(!long.is_inline());
+        assert_eq!(long.slice(..22).to_vec().len(), 22);
+        assert!(long.slice(..22).to_vec().is_inline());
+        assert_eq!(long.slice(..22), long.slice(..22).to_vec());
+
+        let mut spilled = long.clone();
+        spilled.truncate(3);
+        assert!(!spilled.is_inline());
+        assert_eq!(spilled, long.slice(..3).to_vec());
+        assert!(spilled.clone().is_inline());
+
+        let (inner, len) = long.clone().into_raw();
+        // SAFETY: inner and len come from a valid CologneVec
+        let raw = unsafe { CologneVec::from_raw(inner, len) };
+        assert_eq!(raw, long);
+        // The given storage is kept on the heap even if it would fit inline
+        assert!(!CologneVec::try_from_raw(vec![0x12], 2).unwrap().is_inline());
+        assert!(!CologneVec::from_inner(Vec::with_capacity(4)).is_inline());
+    }
 }
@@ -0,0 +1,58 @@
+//! Errors for the validation of untrusted cologne codes.
+
+use core::fmt;
+
+/// Error returned when untrusted data does not contain valid [`CologneCode`](crate::CologneCode)s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InvalidCode {
+    /// The nibble at code index `index` has the value `value` which is no valid code.
+    Nibble {
+        /// Index of the invalid code
+        index: usize,
+        /// Value of the invalid nibble
+        value: u8,
+    },
+    /// The character at byte index `index` is no valid code character.
+    Char {
+        /// Byte index of the invalid character
+        index: usize,
+        /// The invalid character
+        value: char,
+    },
+    /// The backing storage has `bytes` bytes, which does not match a len of `len` codes.
+    Length {
+        /// The number of codes
+        len: usize,
+        /// The number of bytes of the backing storage
+        bytes: usize,
+    },
+    /// A single code was expected but the input was empty.
+    Empty,
+    /// The unused low nibble of the last byte has the value `value` instead of `0`.
+    Padding {
+        /// Value of the padding nibble
+        value: u8,
+    },
+}
+
+impl fmt::Display for InvalidCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nibble { index, value } => {
+                write!(f, "invalid cologne code nibble {:#x} at index {}", value, index)
+            }
+            Self::Char { index, value } => {
+                write!(f, "invalid cologne code character {:?} at index {}", value, index)
+            }
+            Self::Length { len, bytes } => {
+                write!(f, "{} bytes can't store exactly {} cologne codes", bytes, len)
+            }
+            Self::Empty => write!(f, "expected a cologne code but the input was empty"),
+            Self::Padding { value } => {
+                write!(f, "padding nibble is {:#x} instead of 0", value)
+            }
+        }
+    }
+}
+
+impl core::error::Error for InvalidCode {}
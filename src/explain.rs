@@ -0,0 +1,461 @@
+//! Tracing of the rules the encoder applies to every character.
+
+use core::{fmt, mem, ops::Range};
+
+use alloc::{format, string::String, vec::Vec};
+
+use crate::{
+    sink::{Encoder, Push, PushRules},
+    *,
+};
+
+/// The rule of the cologne phonetics which was applied to a character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// The letter always has the same code, e.g. `B` is always `1` and vowels are `0`
+    Fixed,
+    /// `H` has no code
+    Silent,
+    /// `C` at the start of a word before `A`, `H`, `K`, `L`, `O`, `Q`, `R`, `U` or `X` is `4`
+    CInitialHard,
+    /// `C` at the start of a word before any other letter is `8`
+    CInitialSoft,
+    /// `C` after `S` or `Z` is `8`
+    CAfterSZ,
+    /// `C` before `A`, `H`, `K`, `O`, `Q`, `U` or `X` is `4`
+    CHard,
+    /// `C` in any other position is `8`
+    CSoft,
+    /// `D` or `T` before `C`, `S` or `Z` is `8`
+    DTBeforeSibilant,
+    /// `D` or `T` in any other position is `2`
+    DT,
+    /// `P` before `H` is `3`
+    PH,
+    /// `P` in any other position is `1`
+    P,
+    /// `X` after `C`, `K` or `Q` is `8`
+    XAfterCKQ,
+    /// `X` in any other position is `48`
+    X,
+    /// Whitespace or breaking punctuation ends the word with a [`CologneCode::Space`]
+    Break,
+    /// A lowercase to uppercase transition ends the word with a [`CologneCode::Space`], see
+    /// [`EncodeOptions::split_camel_case`]
+    CamelCase,
+    /// Joining punctuation has no code but separates the letters on both sides, see
+    /// [`PunctuationMode::Join`]
+    Join,
+    /// The encoder skips the character, e.g. ignored punctuation, see [`PunctuationMode::Ignore`]
+    Ignored,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Fixed => "fixed code",
+            Self::Silent => "H has no code",
+            Self::CInitialHard => "C at word start before A, H, K, L, O, Q, R, U, X",
+            Self::CInitialSoft => "C at word start before other letters",
+            Self::CAfterSZ => "C after S, Z",
+            Self::CHard => "C before A, H, K, O, Q, U, X",
+            Self::CSoft => "C in other positions",
+            Self::DTBeforeSibilant => "D, T before C, S, Z",
+            Self::DT => "D, T in other positions",
+            Self::PH => "P before H",
+            Self::P => "P in other positions",
+            Self::XAfterCKQ => "X after C, K, Q",
+            Self::X => "X in other positions",
+            Self::Break => "word break",
+            Self::CamelCase => "camel case word break",
+            Self::Join => "joins words",
+            Self::Ignored => "ignored",
+        })
+    }
+}
+
+/// What happened to a code after a [`Rule`] produced it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// The code is part of the output
+    Kept,
+    /// The code was removed as it is equal to the previous code
+    Deduplicated,
+    /// The `0` was removed as it is not at the start of a word
+    ZeroRemoved,
+    /// The [`CologneCode::Space`] was removed as it is at the start or end of the output
+    BreakRemoved,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Kept => "kept",
+            Self::Deduplicated => "duplicate",
+            Self::ZeroRemoved => "zero removed",
+            Self::BreakRemoved => "break removed",
+        })
+    }
+}
+
+/// The encoding of one character of the input, see [`explain`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Step {
+    /// The source bytes of the character, the span of a [`Rule::CamelCase`] step is empty and
+    /// starts at the uppercase letter
+    pub span: Range<usize>,
+    /// The character
+    pub letter: char,
+    /// The applied rule
+    pub rule: Rule,
+    /// The codes the rule produced and what happened to every code
+    pub codes: Vec<(CologneCode, Outcome)>,
+}
+
+impl Step {
+    /// Get a [`Display`](fmt::Display)able table of `steps` with one row per step.
+    pub fn table(steps: &[Step]) -> StepTable<'_> {
+        StepTable { steps }
+    }
+}
+
+/// Table of [`Step`]s returned by [`Step::table`].
+#[derive(Debug, Clone, Copy)]
+pub struct StepTable<'a> {
+    /// The rows of the table
+    steps: &'a [Step],
+}
+
+impl fmt::Display for StepTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<[String; 5]> = self
+            .steps
+            .iter()
+            .map(|step| {
+                let mut codes: String = step.codes.iter().map(|(c, _)| c.as_char()).collect();
+                let mut outcomes = step
+                    .codes
+                    .iter()
+                    .map(|(_, o)| format!("{}", o))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if step.codes.is_empty() {
+                    codes.push('-');
+                    outcomes.push('-');
+                }
+                let letter = match step.rule {
+                    Rule::CamelCase => String::new(),
+                    _ => format!("{:?}", step.letter),
+                };
+                [
+                    format!("{:?}", step.span),
+                    letter,
+                    codes,
+                    outcomes,
+                    format!("{}", step.rule),
+                ]
+            })
+            .collect();
+        let header = ["bytes", "char", "codes", "result", "rule"];
+        let mut widths = header.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut write_row = |row: [&str; 5]| {
+            for (i, (cell, width)) in row.iter().zip(widths).enumerate() {
+                if i + 1 == row.len() {
+                    writeln!(f, "{}", cell)?;
+                } else {
+                    write!(f, "{:<width$}  ", cell, width = width)?;
+                }
+            }
+            Ok(())
+        };
+        write_row(header)?;
+        for row in &rows {
+            write_row(row.each_ref().map(String::as_str))?;
+        }
+        Ok(())
+    }
+}
+
+/// Trace the encoding of `input` character by character, see [`explain_with_options`].
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{explain, Outcome, Rule, Step, CologneCode};
+/// let steps = explain("Chemnitz");
+/// assert_eq!(steps[0].rule, Rule::CInitialHard);
+/// assert_eq!(steps[0].codes, [(CologneCode::Class4, Outcome::Kept)]);
+/// assert_eq!(steps[2].codes, [(CologneCode::Class0, Outcome::ZeroRemoved)]);
+/// assert_eq!(steps[6].rule, Rule::DTBeforeSibilant);
+/// assert_eq!(steps[7].codes, [(CologneCode::Class8, Outcome::Deduplicated)]);
+/// println!("{}", Step::table(&steps));
+/// ```
+pub fn explain(input: &str) -> Vec<Step> {
+    explain_with_options(input, &EncodeOptions::DEFAULT)
+}
+
+/// Trace the encoding of `input` using the given [`EncodeOptions`].
+///
+/// Returns one [`Step`] per character of the input plus one [`Rule::CamelCase`] step per camel
+/// case word break. The [`Outcome::Kept`] codes of all steps are exactly the encoded codes.
+pub fn explain_with_options(input: &str, opts: &EncodeOptions) -> Vec<Step> {
+    let mut sink = ExplainSink {
+        rules: PushRules::EMPTY,
+        steps: Vec::with_capacity(input.len()),
+        origins: Vec::new(),
+        current: None,
+        flush: None,
+    };
+    // Step index, alphabetic index and previous alphabetic index of every letter, the rule is
+    // chosen after all codes are known
+    let mut letters = Vec::new();
+
+    let mut encoder = Encoder::new();
+    // Step of the uncertain character which is not yet written
+    let mut uncertain = 0;
+
+    for (i, c) in input.char_indices() {
+        let mut current = sink.steps.len();
+        sink.current = Some(current);
+        sink.steps.push(Step {
+            span: i..i + c.len_utf8(),
+            letter: c,
+            rule: Rule::Ignored,
+            codes: Vec::new(),
+        });
+
+        // The alphabetic index the encoder read the character as
+        let mut idx = None;
+        for b in &input.as_bytes()[i..i + c.len_utf8()] {
+            // A pending uncertain character is always written first
+            sink.flush = encoder.is_uncertain().then_some(uncertain);
+            idx = encoder.read(*b, opts, &mut sink).or(idx);
+        }
+
+        match idx {
+            None => (),
+            Some(Idx::SPACE) => sink.steps[current].rule = Rule::Break,
+            Some(Idx::JOIN) => sink.steps[current].rule = Rule::Join,
+            Some(idx) => {
+                // Letters only push spaces when camel case splits the word
+                if let Some((CologneCode::Space, _)) = sink.steps[current].codes.first() {
+                    sink.split_camel_case(current);
+                    current += 1;
+                }
+                letters.push((current, idx, encoder.last()[0]));
+            }
+        }
+        if encoder.is_uncertain() {
+            uncertain = current;
+        }
+    }
+
+    sink.current = None;
+    sink.flush = encoder.is_uncertain().then_some(uncertain);
+    encoder.finish(&mut sink);
+
+    let mut steps = sink.steps;
+    for (step, idx, prev) in letters {
+        let step = &mut steps[step];
+        step.rule = letter_rule(idx, prev, &step.codes);
+    }
+    steps
+}
+
+/// Get the rule which produced `codes` for the letter with the alphabetic index `idx` after the
+/// alphabetic index `prev`, like the rules of [`step!`].
+fn letter_rule(idx: u8, prev: u8, codes: &[(CologneCode, Outcome)]) -> Rule {
+    let first = codes.first().map(|(code, _)| *code);
+    match idx {
+        Idx::H => Rule::Silent,
+        Idx::C => match (prev, first) {
+            (Idx::SPACE, Some(CologneCode::Class4)) => Rule::CInitialHard,
+            (_, Some(CologneCode::Class4)) => Rule::CHard,
+            (Idx::S | Idx::Z, _) => Rule::CAfterSZ,
+            (Idx::SPACE, _) => Rule::CInitialSoft,
+            _ => Rule::CSoft,
+        },
+        Idx::D | Idx::T if first == Some(CologneCode::Class8) => Rule::DTBeforeSibilant,
+        Idx::D | Idx::T => Rule::DT,
+        Idx::P if first == Some(CologneCode::Class3) => Rule::PH,
+        Idx::P => Rule::P,
+        Idx::X if codes.len() == 1 => Rule::XAfterCKQ,
+        Idx::X => Rule::X,
+        _ => Rule::Fixed,
+    }
+}
+
+/// [`CologneSink`] recording every code and its [`Outcome`] in the step which produced it
+struct ExplainSink {
+    /// Rules for the next code
+    rules: PushRules,
+    /// All steps so far
+    steps: Vec<Step>,
+    /// Step index and code index of every encoded code, `None` for the trailing break
+    origins: Vec<Option<(usize, usize)>>,
+    /// Step of the character which is currently read, `None` after the input
+    current: Option<usize>,
+    /// Step of the uncertain character which is written by the next push
+    flush: Option<usize>,
+}
+
+impl ExplainSink {
+    /// Set the outcome of the code at `origin`
+    fn mark(&mut self, origin: Option<(usize, usize)>, outcome: Outcome) {
+        if let Some((step, code)) = origin {
+            self.steps[step].codes[code].1 = outcome;
+        }
+    }
+
+    /// Move the word break the letter at step `current` pushed first into a
+    /// [`Rule::CamelCase`] step inserted before it
+    fn split_camel_case(&mut self, current: usize) {
+        let step = &mut self.steps[current];
+        let camel = Step {
+            span: step.span.start..step.span.start,
+            letter: step.letter,
+            rule: Rule::CamelCase,
+            codes: step.codes.drain(..1).collect(),
+        };
+        self.steps.insert(current, camel);
+        // The other codes of the letter are the last ones written
+        for (step, code) in self.origins.iter_mut().rev().flatten() {
+            if *step != current {
+                break;
+            }
+            if *code > 0 {
+                *step += 1;
+                *code -= 1;
+            }
+        }
+    }
+
+    /// Push `code` to the output and record its outcome
+    fn apply(&mut self, code: CologneCode, origin: Option<(usize, usize)>) {
+        match self.rules.push(code) {
+            Push::Append => self.origins.push(origin),
+            Push::Duplicate => self.mark(origin, Outcome::Deduplicated),
+            Push::LeadingBreak => self.mark(origin, Outcome::BreakRemoved),
+            Push::ReplaceZero => {
+                // The trailing zero was replaced by this code
+                let zero = self
+                    .origins
+                    .last_mut()
+                    .and_then(|o| mem::replace(o, origin));
+                self.mark(zero, Outcome::ZeroRemoved);
+            }
+        }
+    }
+}
+
+impl CologneSink for ExplainSink {
+    fn push(&mut self, code: CologneCode) {
+        let step = self.flush.take().or(self.current);
+        let origin = step.map(|step| {
+            let codes = &mut self.steps[step].codes;
+            codes.push((code, Outcome::Kept));
+            (step, codes.len() - 1)
+        });
+        self.apply(code, origin);
+    }
+
+    fn finish(&mut self) {
+        if let Some(removed) = self.rules.finish() {
+            let origin = self.origins.pop().flatten();
+            let outcome = match removed {
+                CologneCode::Space => Outcome::BreakRemoved,
+                _ => Outcome::ZeroRemoved,
+            };
+            self.mark(origin, outcome);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::tests::{option_sets, INPUTS};
+
+    #[test]
+    fn rules() {
+        let rules = |input: &str| -> Vec<Rule> { explain(input).iter().map(|s| s.rule).collect() };
+        assert_eq!(rules("Ca"), [Rule::CInitialHard, Rule::Fixed]);
+        assert_eq!(rules("Ce"), [Rule::CInitialSoft, Rule::Fixed]);
+        assert_eq!(rules("sc"), [Rule::Fixed, Rule::CAfterSZ]);
+        assert_eq!(rules("ach"), [Rule::Fixed, Rule::CHard, Rule::Silent]);
+        assert_eq!(rules("acl"), [Rule::Fixed, Rule::CSoft, Rule::Fixed]);
+        assert_eq!(rules("ts"), [Rule::DTBeforeSibilant, Rule::Fixed]);
+        assert_eq!(rules("d"), [Rule::DT]);
+        assert_eq!(rules("ph"), [Rule::PH, Rule::Silent]);
+        assert_eq!(rules("p-"), [Rule::P, Rule::Break]);
+        assert_eq!(
+            rules("kx x"),
+            [Rule::Fixed, Rule::XAfterCKQ, Rule::Break, Rule::X]
+        );
+        assert_eq!(rules("é'"), [Rule::Ignored, Rule::Break]);
+    }
+
+    #[test]
+    fn outcomes() {
+        let steps = explain(" Anna h");
+        let codes: Vec<_> = steps.iter().map(|s| s.codes.as_slice()).collect();
+        assert_eq!(
+            codes,
+            [
+                &[(CologneCode::Space, Outcome::BreakRemoved)][..],
+                &[(CologneCode::Class0, Outcome::Kept)],
+                &[(CologneCode::Class6, Outcome::Kept)],
+                &[(CologneCode::Class6, Outcome::Deduplicated)],
+                &[(CologneCode::Class0, Outcome::ZeroRemoved)],
+                &[(CologneCode::Space, Outcome::BreakRemoved)],
+                &[],
+            ]
+        );
+
+        let opts = EncodeOptions::new().split_camel_case(true);
+        let steps = explain_with_options("DietMar", &opts);
+        assert_eq!(steps.len(), 8);
+        assert_eq!(steps[4].rule, Rule::CamelCase);
+        assert_eq!(steps[4].span, 4..4);
+        // The uncertain T is written before the break
+        assert_eq!(steps[3].codes, [(CologneCode::Class2, Outcome::Kept)]);
+        assert_eq!(steps[4].codes, [(CologneCode::Space, Outcome::Kept)]);
+        assert_eq!(steps[5].letter, 'M');
+    }
+
+    #[test]
+    fn kept_codes_are_encoding() {
+        for opts in option_sets() {
+            for input in INPUTS {
+                let steps = explain_with_options(input, &opts);
+                let kept: CologneVec = steps
+                    .iter()
+                    .flat_map(|s| s.codes.iter())
+                    .filter(|(_, o)| *o == Outcome::Kept)
+                    .map(|(c, _)| *c)
+                    .collect();
+                let mut vec = CologneVec::new();
+                vec.read_from_utf8_with_options(input.as_bytes(), &opts);
+                assert_eq!(kept.as_slice(), vec.as_slice(), "{:?}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn table() {
+        let table = Step::table(&explain("Xh")).to_string();
+        assert_eq!(
+            table,
+            "bytes  char  codes  result      rule\n\
+             0..1   'X'   48     kept, kept  X in other positions\n\
+             1..2   'h'   -      -           H has no code\n"
+        );
+    }
+}
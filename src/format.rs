@@ -0,0 +1,154 @@
+//! Configurable textual formatting of [`CologneCode`]s.
+
+use core::fmt;
+
+use crate::*;
+
+/// Builder for alternative textual layouts of [`CologneCode`]s.
+///
+/// The [`Display`](fmt::Display) implementations of [`CologneVec`] and [`CologneSlice`] write
+/// the canonical form `"657 52682"`, which equals the output of
+/// [`utf8_to_cologne_phonetics_string`]. A `CologneFormat` can change the word separator and
+/// pad every word to a fixed width.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// # use cologne_phonetics::{CologneVec, CologneFormat};
+/// let mut buf = CologneVec::new();
+/// buf.read_from_utf8("Müller-Lüdenscheidt".as_bytes());
+/// assert_eq!(buf.to_string(), "657 52682");
+/// assert_eq!(buf.display_with(CologneFormat::new().separator("-")).to_string(), "657-52682");
+/// assert_eq!(buf.display_with(CologneFormat::new().separator("")).to_string(), "65752682");
+/// assert_eq!(
+///     buf.display_with(CologneFormat::new().fixed_width(4)).to_string(),
+///     "6570 5268"
+/// );
+/// assert_eq!(buf.display_with(CologneFormat::lines()).to_string(), "657\n52682");
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CologneFormat<'s> {
+    /// Written between two words
+    separator: &'s str,
+    /// Width every word is padded with zeros or truncated to
+    width: Option<usize>,
+}
+
+impl<'s> CologneFormat<'s> {
+    /// The canonical format, words are separated by a single space.
+    pub const fn new() -> Self {
+        Self {
+            separator: " ",
+            width: None,
+        }
+    }
+
+    /// One word per line.
+    pub const fn lines() -> Self {
+        Self::new().separator("\n")
+    }
+
+    /// Set the string written between two words, an empty string writes all words without any
+    /// separation.
+    pub const fn separator(mut self, separator: &'s str) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Write every word with exactly `width` codes. Shorter words are padded with trailing
+    /// `0`s, longer words are truncated.
+    pub const fn fixed_width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Write every word with its actual length, this is the default.
+    pub const fn variable_width(mut self) -> Self {
+        self.width = None;
+        self
+    }
+
+    /// Create a [`Display`](fmt::Display)able wrapper writing `codes` in this format.
+    pub const fn display<'a>(self, codes: CologneSlice<'a>) -> FormattedCodes<'a, 's> {
+        FormattedCodes {
+            codes,
+            format: self,
+        }
+    }
+}
+
+impl Default for CologneFormat<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// [`CologneCode`]s written in a [`CologneFormat`], created by [`CologneFormat::display`],
+/// [`CologneVec::display_with`] or [`CologneSlice::display_with`].
+#[derive(Debug, Clone, Copy)]
+pub struct FormattedCodes<'a, 's> {
+    /// The codes to write
+    codes: CologneSlice<'a>,
+    /// The format to write them in
+    format: CologneFormat<'s>,
+}
+
+impl fmt::Display for FormattedCodes<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, word) in self.codes.words().enumerate() {
+            if i != 0 {
+                f.write_str(self.format.separator)?;
+            }
+            match self.format.width {
+                Some(width) => {
+                    for code in word.iter().take(width) {
+                        fmt::Write::write_char(f, code.as_char())?;
+                    }
+                    for _ in word.len()..width {
+                        fmt::Write::write_char(f, CologneCode::Class0.as_char())?;
+                    }
+                }
+                None => {
+                    for code in word.iter() {
+                        fmt::Write::write_char(f, code.as_char())?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use alloc::string::{String, ToString};
+
+    use super::*;
+
+    #[test]
+    fn display_matches_string_encoder() {
+        for input in ["", "Wikipedia", "Er kam, Er sah, Er siegte", "aho aho aho"] {
+            let mut text = String::new();
+            utf8_to_cologne_phonetics_string(input.as_bytes(), &mut text);
+            let mut outbuf = CologneVec::new();
+            outbuf.read_from_utf8(input.as_bytes());
+            assert_eq!(outbuf.to_string(), text);
+            assert_eq!(outbuf.as_slice().to_string(), text);
+            assert_eq!(outbuf.display_with(CologneFormat::new()).to_string(), text);
+        }
+    }
+
+    #[test]
+    fn fixed_width() {
+        let mut outbuf = CologneVec::new();
+        outbuf.read_from_utf8("Er kam, Er sah, Er siegte".as_bytes());
+        let format = CologneFormat::new().fixed_width(2).separator(",");
+        assert_eq!(outbuf.display_with(format).to_string(), "07,46,07,80,07,84");
+        let format = CologneFormat::lines().fixed_width(0);
+        assert_eq!(outbuf.display_with(format).to_string(), "\n\n\n\n\n");
+        let format = format.variable_width();
+        assert_eq!(outbuf.display_with(format).to_string(), "07\n46\n07\n8\n07\n842");
+        assert_eq!(CologneVec::new().display_with(format).to_string(), "");
+    }
+}
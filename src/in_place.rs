@@ -0,0 +1,173 @@
+//! Encoding that reuses the input buffer for the output.
+
+use alloc::vec::Vec;
+
+use crate::{
+    sink::{CodeBuffer, Encoder, IndexSink},
+    to_slice::write_code,
+    *,
+};
+
+/// Minimum number of bytes inserted in front of the unread input when the output catches up
+const MIN_GAP: usize = 16;
+
+/// Encode the utf8 bytes in `buf` and overwrite them with the ASCII digits and spaces of the
+/// codes, like the [`Display`] implementation of [`CologneSlice`].
+///
+/// The output is usually shorter than the input, so no allocation happens. Only if `X`
+/// expansions (see [`max_encoded_len`]) make the output longer than the bytes read so far, the
+/// unread input is moved back to make room.
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::encode_in_place;
+/// let mut buf = "Müller-Lüdenscheidt".as_bytes().to_vec();
+/// encode_in_place(&mut buf);
+/// assert_eq!(buf, b"657 52682");
+/// ```
+///
+/// [`Display`]: core::fmt::Display
+pub fn encode_in_place(buf: &mut Vec<u8>) {
+    encode_in_place_with_options(buf, &EncodeOptions::DEFAULT)
+}
+
+/// [`encode_in_place`] using the given [`EncodeOptions`].
+pub fn encode_in_place_with_options(buf: &mut Vec<u8>, opts: &EncodeOptions) {
+    encode_in_place_impl(buf, false, opts);
+}
+
+/// Encode the utf8 bytes in `buf` and overwrite them with the packed codes, like the raw
+/// storage of a [`CologneVec`]. Returns the number of codes, `buf` is truncated to
+/// `len.div_ceil(2)` bytes.
+///
+/// See [`encode_in_place`] for how the buffer is reused. [`CologneVec::from_utf8_in_place`]
+/// wraps the result into a [`CologneVec`].
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::encode_in_place_packed;
+/// let mut buf = b"Wikipedia".to_vec();
+/// assert_eq!(encode_in_place_packed(&mut buf), 4);
+/// assert_eq!(buf, [0x34, 0x12]);
+/// ```
+pub fn encode_in_place_packed(buf: &mut Vec<u8>) -> usize {
+    encode_in_place_packed_with_options(buf, &EncodeOptions::DEFAULT)
+}
+
+/// [`encode_in_place_packed`] using the given [`EncodeOptions`].
+pub fn encode_in_place_packed_with_options(buf: &mut Vec<u8>, opts: &EncodeOptions) -> usize {
+    encode_in_place_impl(buf, true, opts)
+}
+
+impl CologneVec {
+    /// Encode the utf8 bytes in `buf` reusing its allocation for the `CologneVec`, see
+    /// [`encode_in_place_packed`].
+    ///
+    /// # Example
+    /// ```
+    /// # use cologne_phonetics::CologneVec;
+    /// let vec = CologneVec::from_utf8_in_place("Müller-Lüdenscheidt".as_bytes().to_vec());
+    /// assert_eq!(vec.to_string(), "657 52682");
+    /// ```
+    pub fn from_utf8_in_place(mut buf: Vec<u8>) -> CologneVec {
+        let len = encode_in_place_packed(&mut buf);
+        // SAFETY: The first len nibbles were written from valid codes and the padding nibble is
+        // zero
+        unsafe { CologneVec::from_raw(buf, len) }
+    }
+}
+
+/// Run the encoder over `buf` writing the codes into `buf` and return the number of codes
+fn encode_in_place_impl(buf: &mut Vec<u8>, packed: bool, opts: &EncodeOptions) -> usize {
+    let mut sink = IndexSink::new(InPlaceBuffer {
+        buf,
+        next: 0,
+        packed,
+    });
+
+    let mut encoder = Encoder::new();
+    while let Some(&b) = sink.out.buf.get(sink.out.next) {
+        sink.out.next += 1;
+        encoder.read(b, opts, &mut sink);
+    }
+    encoder.finish(&mut sink);
+
+    let byte_len = if packed {
+        sink.len.div_ceil(2)
+    } else {
+        sink.len
+    };
+    sink.out.buf.truncate(byte_len);
+    sink.len
+}
+
+/// [`CodeBuffer`] writing into the buffer it reads from.
+///
+/// The bytes `..next` of `buf` were read and may be overwritten, the written codes always end
+/// before `next`.
+struct InPlaceBuffer<'a> {
+    /// The input and output buffer
+    buf: &'a mut Vec<u8>,
+    /// Index of the next unread input byte
+    next: usize,
+    /// Wether the codes are packed into nibbles instead of ASCII
+    packed: bool,
+}
+
+impl CodeBuffer for InPlaceBuffer<'_> {
+    fn write(&mut self, idx: usize, code: CologneCode) {
+        let byte_idx = if self.packed { idx >> 1 } else { idx };
+        if byte_idx == self.next {
+            // The output caught up with the unread input, move the input back by a gap which
+            // grows with the input to keep repeated expansions linear
+            let gap = self.next.max(MIN_GAP);
+            self.buf
+                .splice(self.next..self.next, core::iter::repeat_n(0, gap));
+            self.next += gap;
+        }
+        write_code(self.buf, self.packed, idx, code);
+    }
+
+    fn remove(&mut self, idx: usize) {
+        // Reset the nibble to the zero padding
+        if self.packed && idx & 0x01 == 1 {
+            self.write(idx, CologneCode::Class0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::tests::{option_sets, INPUTS};
+
+    #[test]
+    fn same_as_vec() {
+        for opts in option_sets() {
+            for input in INPUTS {
+                let mut vec = CologneVec::new();
+                vec.read_from_utf8_with_options(input.as_bytes(), &opts);
+
+                let mut buf = input.as_bytes().to_vec();
+                encode_in_place_with_options(&mut buf, &opts);
+                assert_eq!(buf, vec.to_string().as_bytes(), "{:?}", input);
+
+                let mut buf = input.as_bytes().to_vec();
+                let len = encode_in_place_packed_with_options(&mut buf, &opts);
+                assert_eq!(len, vec.len(), "{:?}", input);
+                assert_eq!(buf, vec.get_raw(), "{:?}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn reuses_allocation() {
+        let buf = "Müller-Lüdenscheidt".as_bytes().to_vec();
+        let ptr = buf.as_ptr();
+        let vec = CologneVec::from_utf8_in_place(buf);
+        assert_eq!(vec.to_string(), "657 52682");
+        assert_eq!(vec.into_raw().0.as_ptr(), ptr);
+    }
+}
@@ -0,0 +1,381 @@
+//! Keys for [`CologneCode`]s that sort lexicographically, for use in ordered maps and
+//! key-value stores.
+
+use core::{cmp::Ordering, fmt, ops::Deref};
+
+use alloc::vec::Vec;
+
+use crate::*;
+
+impl CologneSlice<'_> {
+    /// Encode this slice as a byte key which sorts like the codes.
+    ///
+    /// Every code is stored as one byte with its [`CologneCode::get`] value, so comparing two
+    /// keys byte-wise yields the same order as comparing the codes lexicographically and the key
+    /// of a prefix of the codes is a byte prefix of the full key. This allows phonetic prefix
+    /// scans in key-value stores with byte ordered keys. The key uses twice the space of the
+    /// packed representation, see [`to_bytes`](CologneSlice::to_bytes) for a compact format
+    /// which does not preserve the order.
+    ///
+    /// # Example
+    /// ```
+    /// # use cologne_phonetics::CologneVec;
+    /// let mut meier = CologneVec::new();
+    /// meier.read_from_utf8(b"Meier Hans");
+    /// let mut mueller = CologneVec::new();
+    /// mueller.read_from_utf8(b"Mueller");
+    /// assert!(meier.as_slice() > mueller.as_slice());
+    /// assert!(meier.to_ordered_key() > mueller.to_ordered_key());
+    /// assert!(meier.to_ordered_key().starts_with(&meier.slice(..2).to_ordered_key()));
+    /// ```
+    pub fn to_ordered_key(&self) -> Vec<u8> {
+        self.iter().map(CologneCode::get).collect()
+    }
+}
+
+/// Pack up to `max` codes into the high nibbles of an integer, returns `None` if there are more
+/// than `max` codes. Every code is stored as its value + 1 and unused nibbles are `0` so
+/// shorter keys sort before longer keys with the same prefix.
+fn pack_key(codes: &CologneSlice<'_>, max: usize) -> Option<u128> {
+    if codes.len() > max {
+        return None;
+    }
+    let mut key = 0u128;
+    for (i, code) in codes.iter().enumerate() {
+        let shift = (max - 1 - i) * 4;
+        key |= u128::from(code.get() + 1) << shift;
+    }
+    Some(key)
+}
+
+/// Reverse of [`pack_key`]
+fn unpack_key(key: u128, max: usize) -> Result<CologneVec, InvalidCode> {
+    let mut me = CologneVec::with_capacity(max.div_ceil(2));
+    let mut end = false;
+    for index in 0..max {
+        let shift = (max - 1 - index) * 4;
+        let nibble = ((key >> shift) & 0x0f) as u8;
+        if nibble == 0 {
+            end = true;
+        } else if end {
+            return Err(InvalidCode::Padding { value: nibble });
+        } else {
+            // Errors report the stored nibble, not the code value nibble - 1
+            let code = CologneCode::from_nibble(nibble - 1).ok_or(InvalidCode::Nibble {
+                index,
+                value: nibble,
+            })?;
+            me.push_raw(code);
+        }
+    }
+    Ok(me)
+}
+
+impl CologneSlice<'_> {
+    /// Pack up to 16 codes into an `u64`, returns `None` if there are more codes.
+    ///
+    /// Every code is stored in a nibble, starting with the most significant one, as its
+    /// [`CologneCode::get`] value + 1. Unused nibbles are `0`, so the integer order of the keys
+    /// equals the lexicographic order of the codes.
+    ///
+    /// # Example
+    /// ```
+    /// # use cologne_phonetics::CologneVec;
+    /// let mut buf = CologneVec::new();
+    /// buf.read_from_utf8(b"Wikipedia");
+    /// let key = buf.to_u64_key().unwrap();
+    /// assert_eq!(key, 0x4523_0000_0000_0000);
+    /// assert_eq!(CologneVec::from_u64_key(key), Ok(buf));
+    /// ```
+    pub fn to_u64_key(&self) -> Option<u64> {
+        pack_key(self, 16).map(|key| key as u64)
+    }
+
+    /// Pack up to 32 codes into an `u128`, returns `None` if there are more codes. See
+    /// [`to_u64_key`](Self::to_u64_key) for the layout.
+    pub fn to_u128_key(&self) -> Option<u128> {
+        pack_key(self, 32)
+    }
+}
+
+impl CologneVec {
+    /// Pack up to 16 codes into an `u64`, see [`CologneSlice::to_u64_key`].
+    pub fn to_u64_key(&self) -> Option<u64> {
+        self.as_slice().to_u64_key()
+    }
+
+    /// Pack up to 32 codes into an `u128`, see [`CologneSlice::to_u64_key`].
+    pub fn to_u128_key(&self) -> Option<u128> {
+        self.as_slice().to_u128_key()
+    }
+
+    /// Decode a key created by [`to_u64_key`](Self::to_u64_key). The `index` of a returned
+    /// error is the nibble index counted from the most significant nibble and its `value` is the
+    /// stored nibble.
+    pub fn from_u64_key(key: u64) -> Result<CologneVec, InvalidCode> {
+        unpack_key(u128::from(key), 16)
+    }
+
+    /// Decode a key created by [`to_u128_key`](Self::to_u128_key). The `index` of a returned
+    /// error is the nibble index counted from the most significant nibble and its `value` is the
+    /// stored nibble.
+    pub fn from_u128_key(key: u128) -> Result<CologneVec, InvalidCode> {
+        unpack_key(key, 32)
+    }
+
+    /// Encode this `CologneVec` as a byte key which sorts like the codes, see
+    /// [`CologneSlice::to_ordered_key`].
+    pub fn to_ordered_key(&self) -> Vec<u8> {
+        self.as_slice().to_ordered_key()
+    }
+
+    /// Decode a key created by [`to_ordered_key`](CologneSlice::to_ordered_key).
+    pub fn from_ordered_key(key: &[u8]) -> Result<CologneVec, InvalidCode> {
+        let mut me = CologneVec::with_capacity(key.len().div_ceil(2));
+        for (index, b) in key.iter().enumerate() {
+            let code =
+                CologneCode::from_nibble(*b).ok_or(InvalidCode::Nibble { index, value: *b })?;
+            me.push_raw(code);
+        }
+        Ok(me)
+    }
+
+    /// Compare the codes of `self` and `other` lexicographically.
+    ///
+    /// The [`Ord`] implementation of `CologneVec` compares the len first, use this function or
+    /// the [`Lexicographic`] wrapper to sort like the codes.
+    pub fn cmp_lexicographic(&self, other: &CologneVec) -> Ordering {
+        self.as_slice().cmp(&other.as_slice())
+    }
+}
+
+/// Wrapper around a [`CologneVec`] which is ordered lexicographically by its codes instead of
+/// by its len first, e.g. to be used as key in a `BTreeMap` for phonetic range queries.
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{CologneVec, Lexicographic};
+/// let mut short = CologneVec::new();
+/// short.read_from_utf8(b"Zott");
+/// let mut long = CologneVec::new();
+/// long.read_from_utf8(b"Bauerhof");
+/// assert!(short < long);
+/// assert!(Lexicographic(short) > Lexicographic(long));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Lexicographic(pub CologneVec);
+
+impl PartialOrd for Lexicographic {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Lexicographic {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp_lexicographic(&other.0)
+    }
+}
+
+impl Deref for Lexicographic {
+    type Target = CologneVec;
+
+    fn deref(&self) -> &CologneVec {
+        &self.0
+    }
+}
+
+impl From<CologneVec> for Lexicographic {
+    fn from(vec: CologneVec) -> Self {
+        Self(vec)
+    }
+}
+
+/// Up to 16 [`CologneCode`]s packed into an `u64`, see [`CologneSlice::to_u64_key`].
+///
+/// `CologneKey64` is a cheap key for hash maps and integer database columns, it is ordered
+/// lexicographically by its codes and displayed like a [`CologneVec`].
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{CologneVec, CologneKey64};
+/// let mut buf = CologneVec::new();
+/// buf.read_from_utf8(b"Max Mustermann");
+/// let key = CologneKey64::new(&buf).unwrap();
+/// assert_eq!(key.to_string(), "648 682766");
+/// assert_eq!(key.len(), 10);
+/// assert_eq!(key.to_vec(), buf);
+/// assert_eq!(CologneKey64::from_u64(key.get()), Ok(key));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct CologneKey64(u64);
+
+impl CologneKey64 {
+    /// Create a key from the given codes, returns `None` if there are more than 16 codes.
+    pub fn new(codes: &CologneVec) -> Option<Self> {
+        codes.to_u64_key().map(Self)
+    }
+
+    /// Create a key from an integer created by [`get`](Self::get).
+    pub fn from_u64(key: u64) -> Result<Self, InvalidCode> {
+        CologneVec::from_u64_key(key).map(|_| Self(key))
+    }
+
+    /// Get the integer value of this key.
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// Get the number of codes stored in this key.
+    pub const fn len(self) -> usize {
+        (16 - self.0.trailing_zeros() / 4) as usize
+    }
+
+    /// Check if this key contains no codes.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterate the codes of this key.
+    pub fn iter(self) -> impl DoubleEndedIterator<Item = CologneCode> + ExactSizeIterator {
+        (0..self.len()).map(move |i| {
+            let value = ((self.0 >> ((15 - i) * 4)) & 0x0f) as u8;
+            // SAFETY: The key was validated on creation
+            unsafe { nibble_to_cologne(value - 1) }
+        })
+    }
+
+    /// Unpack the codes into a [`CologneVec`].
+    pub fn to_vec(self) -> CologneVec {
+        self.iter().collect()
+    }
+}
+
+impl From<CologneKey64> for u64 {
+    fn from(key: CologneKey64) -> u64 {
+        key.0
+    }
+}
+
+impl fmt::Display for CologneKey64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for code in self.iter() {
+            fmt::Write::write_char(f, code.as_char())?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for CologneKey64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CologneKey64({:#018x}: [{}])", self.0, self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn ordered_key() {
+        let inputs = [
+            "", "a", "ab", "Wikipedia", "Meier", "Meier Hans", "Maurer", "Mueller", "Xaver",
+            "Er kam, Er sah, Er siegte", "aho aho aho", "Ott", "Otto Bauer",
+        ];
+        let vecs: Vec<CologneVec> = inputs
+            .iter()
+            .map(|input| {
+                let mut vec = CologneVec::new();
+                vec.read_from_utf8(input.as_bytes());
+                vec
+            })
+            .collect();
+        for a in &vecs {
+            let key = a.to_ordered_key();
+            assert_eq!(CologneVec::from_ordered_key(&key).as_ref(), Ok(a));
+            for len in 0..=a.len() {
+                assert!(key.starts_with(&a.slice(..len).to_ordered_key()));
+            }
+            for b in &vecs {
+                let expected = a.iter().cmp(b.iter());
+                assert_eq!(key.cmp(&b.to_ordered_key()), expected);
+                assert_eq!(a.cmp_lexicographic(b), expected);
+                assert_eq!(Lexicographic(a.clone()).cmp(&Lexicographic(b.clone())), expected);
+            }
+        }
+        assert_eq!(
+            CologneVec::from_ordered_key(&[1, 2, 9]),
+            Err(InvalidCode::Nibble { index: 2, value: 9 })
+        );
+    }
+
+    #[test]
+    fn integer_keys() {
+        let inputs = [
+            "", "a", "ab", "Wikipedia", "Meier", "Meier Hans", "Maurer", "Mueller", "Xaver",
+            "Er kam, Er sah", "aho aho aho", "Ott", "Otto Bauer", "Max Mustermann",
+        ];
+        let vecs: Vec<CologneVec> = inputs
+            .iter()
+            .map(|input| {
+                let mut vec = CologneVec::new();
+                vec.read_from_utf8(input.as_bytes());
+                vec
+            })
+            .collect();
+        for a in &vecs {
+            let key = a.to_u64_key().unwrap();
+            assert_eq!(CologneVec::from_u64_key(key).as_ref(), Ok(a));
+            let key128 = a.to_u128_key().unwrap();
+            assert_eq!(CologneVec::from_u128_key(key128).as_ref(), Ok(a));
+            let key64 = CologneKey64::new(a).unwrap();
+            assert_eq!(key64.len(), a.len());
+            assert_eq!(key64.to_string(), a.to_string());
+            assert_eq!(key64.to_vec(), *a);
+            for b in &vecs {
+                let expected = a.cmp_lexicographic(b);
+                assert_eq!(key.cmp(&b.to_u64_key().unwrap()), expected);
+                assert_eq!(key128.cmp(&b.to_u128_key().unwrap()), expected);
+                assert_eq!(key64.cmp(&CologneKey64::new(b).unwrap()), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn integer_key_limits() {
+        let full: CologneVec = core::iter::repeat_n(CologneCode::Space, 16).collect();
+        assert_eq!(full.to_u64_key(), Some(u64::MAX));
+        assert_eq!(CologneKey64::new(&full).unwrap().len(), 16);
+        let mut long = full.clone();
+        long.extend([CologneCode::Class1]);
+        assert_eq!(long.to_u64_key(), None);
+        assert_eq!(CologneKey64::new(&long), None);
+        let key = long.to_u128_key().unwrap();
+        assert_eq!(CologneVec::from_u128_key(key), Ok(long));
+
+        assert_eq!(
+            CologneVec::from_u64_key(0x1a00_0000_0000_0000),
+            Err(InvalidCode::Nibble { index: 1, value: 0xa })
+        );
+        assert_eq!(
+            CologneVec::from_u64_key(0x1020_0000_0000_0000),
+            Err(InvalidCode::Padding { value: 2 })
+        );
+        assert!(CologneKey64::from_u64(0x1020_0000_0000_0000).is_err());
+    }
+
+    #[test]
+    fn invalid_key_nibble() {
+        // Stored nibbles are the code value + 1, so 0xa..=0xe are no codes
+        for nibble in 0xa..=0xe_u8 {
+            let err = InvalidCode::Nibble { index: 2, value: nibble };
+            let key = 0x12 << 56 | u64::from(nibble) << 52;
+            assert_eq!(CologneVec::from_u64_key(key), Err(err));
+            assert_eq!(CologneVec::from_u128_key(u128::from(key) << 64), Err(err));
+            assert_eq!(CologneKey64::from_u64(key), Err(err));
+        }
+        assert_eq!(CologneVec::from_u64_key(0x12f0_0000_0000_0000).unwrap().len(), 3);
+    }
+}
@@ -6,6 +6,7 @@
 //!
 //! # Example
 //! ```
+//! # #[cfg(feature = "alloc")] {
 //! # use cologne_phonetics::{CologneVec, CologneCode};
 //! let mut buf = CologneVec::new();
 //! buf.read_from_utf8("Marius Macher".as_bytes());
@@ -18,20 +19,95 @@
 //!     CologneCode::Class4,
 //!     CologneCode::Class7,
 //! ]))
+//! # }
 //! ```
+//!
+//! # Features
+//! - `std` (default): `std::io` support, implies `alloc`.
+//! - `alloc` (default): [`CologneVec`], the `Vec` and `String` based encoding functions and
+//!   everything else which needs a heap. Without it the crate is usable on targets without an
+//!   allocator through [`CologneArray`], [`encode_packed`], [`encode_to_slice`] and
+//!   [`encode_into`], which produce the same codes. `cargo test --no-default-features` checks
+//!   this configuration and `cargo test --no-default-features --features alloc` the one with an
+//!   allocator but without `std`.
+//! - `serde`: `Serialize` and `Deserialize` implementations, implies `alloc`.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[cfg(feature = "alloc")]
 mod string;
+#[cfg(feature = "alloc")]
+mod align;
+mod array;
+#[cfg(feature = "alloc")]
 mod cologne_vec;
-#[cfg(test)]
+mod code_iter;
+mod error;
+#[cfg(feature = "alloc")]
+mod explain;
+mod format;
+#[cfg(feature = "alloc")]
+mod in_place;
+#[cfg(feature = "alloc")]
+mod key;
+mod options;
+#[cfg(feature = "alloc")]
+mod raw;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod sink;
+mod slice;
+#[cfg(feature = "alloc")]
+mod spans;
+#[cfg(feature = "alloc")]
+mod storage;
+mod to_slice;
+#[cfg(feature = "alloc")]
+mod vowels;
+mod wire;
+#[cfg(all(test, feature = "alloc"))]
 mod tests;
 
+#[cfg(feature = "alloc")]
+pub use align::{align, align_with_options, AlignedCode, Edit};
+pub use array::{encode_packed, CapacityError, CologneArray, OverflowPolicy};
+#[cfg(feature = "alloc")]
 pub use cologne_vec::CologneVec;
-pub use string::utf8_to_cologne_phonetics_string;
+#[cfg(feature = "alloc")]
+pub use code_iter::IntoIter;
+pub use code_iter::Iter;
+pub use error::InvalidCode;
+#[cfg(feature = "alloc")]
+pub use explain::{explain, explain_with_options, Outcome, Rule, Step, StepTable};
+pub use format::{CologneFormat, FormattedCodes};
+#[cfg(feature = "alloc")]
+pub use in_place::{
+    encode_in_place, encode_in_place_packed, encode_in_place_packed_with_options,
+    encode_in_place_with_options,
+};
+#[cfg(feature = "alloc")]
+pub use key::{CologneKey64, Lexicographic};
+pub use options::{EncodeOptions, PunctuationMode};
+#[cfg(feature = "alloc")]
+pub use raw::{collapse, encode_raw, encode_raw_with_options, remove_zeros};
+pub use sink::{encode_into, CologneSink};
+pub use slice::{CologneSlice, Words};
+#[cfg(feature = "alloc")]
+pub use spans::{encode_words, SpannedCodes};
+pub use to_slice::{
+    encode_to_slice, encode_to_slice_packed, encode_to_slice_packed_with_options,
+    encode_to_slice_with_options, max_encoded_len, BufferTooSmall,
+};
+#[cfg(feature = "alloc")]
+pub use vowels::VowelCodes;
+pub use wire::{WireError, WIRE_FORMAT_VERSION};
+#[cfg(feature = "alloc")]
+pub use string::{utf8_to_cologne_phonetics_string, utf8_to_cologne_phonetics_string_with_options};
 
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
 use core::{hint, mem};
 
@@ -53,12 +129,13 @@ const UNCERTAIN_X: u8 = 13;
 
 #[rustfmt::skip]
 /// Lookups for the cologne codes as numbers uncertain characters are mapped to other numbers:
-/// 9 is 'C', 10 is 'D' or 'T', 11 is 'H', 12 is 'P', 13 is 'X', Space characters are 14
-const CHARACTER_TO_CODE: [u8; 27] = [
+/// 9 is 'C', 10 is 'D' or 'T', 11 is 'H' or a joining character, 12 is 'P', 13 is 'X',
+/// Space characters are 14
+const CHARACTER_TO_CODE: [u8; 28] = [
     // A  B  C  D  E  F  G  H  I  J  K  L  M
        0, 1, 9, 10,0, 3, 4, 11,0, 0, 4, 5, 6,
-    // N  O  P  Q  R  S  T  U  V  W  X           Y  Z  SPACE
-       6, 0, 12,4, 7, 8, 10,0, 3, 3, UNCERTAIN_X,0, 8, 14,
+    // N  O  P  Q  R  S  T  U  V  W  X           Y  Z  SPACE JOIN
+       6, 0, 12,4, 7, 8, 10,0, 3, 3, UNCERTAIN_X,0, 8, 14,   11,
 ];
 /// Slide the array one to the left
 macro_rules! array_slide {
@@ -69,15 +146,16 @@ macro_rules! array_slide {
 }
 
 /// One iteration of the algorithm to be useable in both the [`CologneVec`] and the
-/// [`utf8_to_cologne_phonetics_vec`] function
+/// [`utf8_to_cologne_phonetics_vec`] function. Evaluates to the alphabetic index (see [`Idx`])
+/// the byte was read as, or `None` if the byte was skipped.
 macro_rules! iter {
-    ($byte: ident, $utf8:ident, $last:ident, $prev_uncertain:ident, $cologne_code_push:path, $outbuf:ident) => {
+    ($byte: ident, $utf8:ident, $last:ident, $prev_uncertain:ident, $prev_lower:ident, $cologne_code_push:path, $outbuf:ident, $opts:expr) => {
         'blk: {
             let mut b = $byte;
 
             if b > 0x7F {
                 $utf8 = b == GERMAN_SPECIAL_CHAR_FIRST_BYTE;
-                break 'blk;
+                break 'blk None;
             }
 
             if $utf8 {
@@ -95,12 +173,39 @@ macro_rules! iter {
                     GERMAN_SZ_SECOND_BYTE => {
                         b = b'Z';
                     }
-                    _ => break 'blk,
+                    _ => break 'blk None,
                 }
             }
 
             // Try to uppercase the letters
+            let raw = b;
             b = lowercase_b(b);
+            if b == Idx::SPACE {
+                match $opts.mode_for(raw) {
+                    PunctuationMode::Break => (),
+                    PunctuationMode::Join => b = Idx::JOIN,
+                    PunctuationMode::Ignore => break 'blk None,
+                }
+            } else if $opts.split_camel_case && $prev_lower && raw.is_ascii_uppercase() {
+                // A lowercase to uppercase transition breaks the word
+                $crate::step!(Idx::SPACE, $last, $prev_uncertain, $cologne_code_push, $outbuf);
+            }
+            $prev_lower = raw.is_ascii_lowercase();
+
+            $crate::step!(b, $last, $prev_uncertain, $cologne_code_push, $outbuf);
+            Some(b)
+        }
+    };
+}
+
+/// Apply the rules for the alphabetic index `b` (see [`Idx`]) given the previous indices in
+/// `$last`.
+macro_rules! step {
+    ($b:expr, $last:ident, $prev_uncertain:ident, $cologne_code_push:path, $outbuf:ident) => {
+        // The state is not read again after the last step
+        #[allow(unused_assignments)]
+        {
+            let b: u8 = $b;
 
             if $prev_uncertain {
                 $prev_uncertain = false;
@@ -154,7 +259,7 @@ macro_rules! iter {
             }
 
             let res = *CHARACTER_TO_CODE.get(usize::from(b)).unwrap_or_else(|| {
-                // SAFETY: b should never by higher than 26 so indexing into the array yields
+                // SAFETY: b should never by higher than 27 so indexing into the array yields
                 // always correct values.
                 unsafe { hint::unreachable_unchecked() }
             });
@@ -200,25 +305,27 @@ fn lowercase_b(b: u8) -> u8 {
 
 pub(crate) use array_slide;
 pub(crate) use iter;
+pub(crate) use step;
 
+#[cfg(feature = "alloc")]
 /// Read the given utf8 bytes into the `outbuf`. Generally you should prefer using a [`CologneVec`]
 pub fn utf8_to_cologne_phonetics_vec(bytes: &[u8], outbuf: &mut Vec<CologneCode>) {
-    let mut utf8 = false;
-    // All values are interpreted as a normal alphabetic character and this maps to their alphabet
-    // index, most ascii punctuation and whitespace characters are 26 and count as a stop
-    let mut last = [26, 26];
-    // Wether the previous character was uncertain and is not yet written
-    let mut prev_uncertain = false;
-
-    for b in bytes {
-        let b = *b;
-        iter!(b, utf8, last, prev_uncertain, cologne_code_push, outbuf);
-    }
+    utf8_to_cologne_phonetics_vec_with_options(bytes, outbuf, &EncodeOptions::DEFAULT)
+}
 
-    cologne_code_push(outbuf, CologneCode::Space);
-    outbuf.pop();
+#[cfg(feature = "alloc")]
+/// Read the given utf8 bytes into the `outbuf` using the given [`EncodeOptions`].
+pub fn utf8_to_cologne_phonetics_vec_with_options(
+    bytes: &[u8],
+    outbuf: &mut Vec<CologneCode>,
+    opts: &EncodeOptions,
+) {
+    let before_last = outbuf.len().checked_sub(2).map(|i| outbuf[i]);
+    let rules = sink::PushRules::after(before_last, outbuf.last().copied());
+    encode_into(bytes, opts, &mut VecSink { outbuf, rules });
 }
 
+#[cfg(feature = "alloc")]
 /// Convert the `Vec<CologneCode>` to a `Vec<u8>` without any iteration or allocation.
 pub fn cologne_code_vec_to_bytevec(mut outbuf: Vec<CologneCode>) -> Vec<u8> {
     let raw_ptr = outbuf.as_mut_ptr();
@@ -229,19 +336,33 @@ pub fn cologne_code_vec_to_bytevec(mut outbuf: Vec<CologneCode>) -> Vec<u8> {
     new_outbuf
 }
 
-/// Push a cologne code to the end of `outbuf`.
-fn cologne_code_push(outbuf: &mut Vec<CologneCode>, code: CologneCode) {
-    // Zero codes not after space must be overwritten
-    if !outbuf.last().is_some_and(|val| *val == code) {
-        match outbuf.get(outbuf.len().saturating_sub(2)..outbuf.len()) {
-            Some(&[CologneCode::Space, CologneCode::Class0]) => (),
-            Some(&[_, CologneCode::Class0]) => {
-                *outbuf.last_mut().unwrap_or_else(|| unreachable!()) = code;
-                return;
+#[cfg(feature = "alloc")]
+/// [`CologneSink`] appending to a `Vec<CologneCode>`
+struct VecSink<'a> {
+    /// The output buffer
+    outbuf: &'a mut Vec<CologneCode>,
+    /// Rules for the next code
+    rules: sink::PushRules,
+}
+
+#[cfg(feature = "alloc")]
+impl CologneSink for VecSink<'_> {
+    fn push(&mut self, code: CologneCode) {
+        match self.rules.push(code) {
+            sink::Push::Append => self.outbuf.push(code),
+            sink::Push::ReplaceZero => {
+                if let Some(last) = self.outbuf.last_mut() {
+                    *last = code;
+                }
             }
-            _ => (),
+            sink::Push::LeadingBreak | sink::Push::Duplicate => (),
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.rules.finish().is_some() {
+            self.outbuf.pop();
         }
-        outbuf.push(code);
     }
 }
 
@@ -250,7 +371,7 @@ fn cologne_code_push(outbuf: &mut Vec<CologneCode>, code: CologneCode) {
 /// # SAFETY:
 /// The given byte must be a valid representation of a [`CologneCode`].
 #[inline(always)]
-unsafe fn nibble_to_cologne(b: u8) -> CologneCode {
+const unsafe fn nibble_to_cologne(b: u8) -> CologneCode {
     mem::transmute(b)
 }
 
@@ -306,6 +427,62 @@ impl CologneCode {
     }
 }
 
+impl CologneCode {
+    /// Convert a nibble as returned by [`get`](Self::get) back to a `CologneCode`, returns
+    /// `None` for invalid values.
+    pub const fn from_nibble(b: u8) -> Option<Self> {
+        match b {
+            0..=8 | 0b1110 => {
+                // SAFETY: 0..=8 and 14 are all valid Cologne codes
+                Some(unsafe { nibble_to_cologne(b) })
+            }
+            _ => None,
+        }
+    }
+
+    /// Convert a character as returned by [`as_char`](Self::as_char) back to a `CologneCode`,
+    /// returns `None` for invalid characters.
+    pub const fn from_char(c: char) -> Option<Self> {
+        match c {
+            '0'..='8' => Self::from_nibble((c as u8).wrapping_sub(b'0')),
+            ' ' => Some(Self::Space),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<u8> for CologneCode {
+    type Error = InvalidCode;
+
+    /// Convert a nibble as returned by [`get`](Self::get) back to a `CologneCode`.
+    fn try_from(value: u8) -> Result<Self, InvalidCode> {
+        Self::from_nibble(value).ok_or(InvalidCode::Nibble { index: 0, value })
+    }
+}
+
+impl TryFrom<char> for CologneCode {
+    type Error = InvalidCode;
+
+    /// Convert a character as returned by [`as_char`](Self::as_char) back to a `CologneCode`.
+    fn try_from(value: char) -> Result<Self, InvalidCode> {
+        Self::from_char(value).ok_or(InvalidCode::Char { index: 0, value })
+    }
+}
+
+impl core::str::FromStr for CologneCode {
+    type Err = InvalidCode;
+
+    /// Parse a single code character as returned by [`as_char`](Self::as_char).
+    fn from_str(s: &str) -> Result<Self, InvalidCode> {
+        let mut chars = s.char_indices();
+        let (_, c) = chars.next().ok_or(InvalidCode::Empty)?;
+        if let Some((index, value)) = chars.next() {
+            return Err(InvalidCode::Char { index, value });
+        }
+        Self::try_from(c)
+    }
+}
+
 impl core::fmt::Display for CologneCode {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.as_char())
@@ -315,9 +492,8 @@ impl core::fmt::Display for CologneCode {
 /// Namespace for alphabetic indices for letters;
 struct Idx;
 
-#[allow(dead_code)]
+#[allow(dead_code, clippy::missing_docs_in_private_items)]
 impl Idx {
-    #![allow(clippy::missing_docs_in_private_items)]
     const A: u8 = 0;
     const B: u8 = 1;
     const C: u8 = 2;
@@ -345,5 +521,6 @@ impl Idx {
     const Y: u8 = 24;
     const Z: u8 = 25;
     const SPACE: u8 = 26;
+    const JOIN: u8 = 27;
 }
 
@@ -0,0 +1,120 @@
+//! Options to customize how the input text is split into words.
+
+/// How a class of punctuation characters is treated while encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PunctuationMode {
+    /// The character breaks the word, a [`CologneCode::Space`](crate::CologneCode::Space) is
+    /// emitted and the following letter is treated as the start of a new word.
+    #[default]
+    Break,
+    /// The character joins the words on both sides into a single word. No space is emitted and
+    /// the following letter is not treated as the start of a word, but the letters on both
+    /// sides are not treated as neighbours by the context rules (e.g. `D` before `S`).
+    Join,
+    /// The character is dropped as if it was never part of the input.
+    Ignore,
+}
+
+/// Options for the encoding of text into [`CologneCode`](crate::CologneCode)s.
+///
+/// Whitespace always breaks words, for the other ascii non letter characters the behaviour can
+/// be chosen per class.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// # use cologne_phonetics::{CologneVec, EncodeOptions, PunctuationMode};
+/// let opts = EncodeOptions::new().hyphen(PunctuationMode::Join);
+/// let mut buf = CologneVec::new();
+/// buf.read_from_utf8_with_options("Müller-Lüdenscheidt".as_bytes(), &opts);
+/// assert_eq!(format!("{:?}", buf), "[65752682]");
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EncodeOptions {
+    /// Treatment of `-`
+    hyphen: PunctuationMode,
+    /// Treatment of `'` and `` ` ``
+    apostrophe: PunctuationMode,
+    /// Treatment of all other ascii punctuation characters and digits
+    other: PunctuationMode,
+    /// Wether a lowercase letter followed by an uppercase letter breaks the word
+    pub(crate) split_camel_case: bool,
+}
+
+impl EncodeOptions {
+    /// The default options, every non letter character breaks words.
+    pub const DEFAULT: Self = Self::new();
+
+    /// Create new options where every non letter character breaks words.
+    pub const fn new() -> Self {
+        Self {
+            hyphen: PunctuationMode::Break,
+            apostrophe: PunctuationMode::Break,
+            other: PunctuationMode::Break,
+            split_camel_case: false,
+        }
+    }
+
+    /// Set how hyphens (`-`) are treated.
+    pub const fn hyphen(mut self, mode: PunctuationMode) -> Self {
+        self.hyphen = mode;
+        self
+    }
+
+    /// Set how apostrophes (`'` and `` ` ``) are treated.
+    pub const fn apostrophe(mut self, mode: PunctuationMode) -> Self {
+        self.apostrophe = mode;
+        self
+    }
+
+    /// Set how all other ascii punctuation characters and digits are treated.
+    pub const fn other(mut self, mode: PunctuationMode) -> Self {
+        self.other = mode;
+        self
+    }
+
+    /// Set wether a lowercase letter followed by an uppercase letter breaks the word, so that
+    /// `"MaxMustermann"` is encoded like `"Max Mustermann"`. Disabled by default.
+    pub const fn split_camel_case(mut self, split: bool) -> Self {
+        self.split_camel_case = split;
+        self
+    }
+
+    /// Get the maximum number of codes an input of `input_len` bytes can be encoded to with
+    /// these options, see [`max_encoded_len`](crate::max_encoded_len).
+    ///
+    /// Splitting camel case words adds a space in front of every other letter at most, so the
+    /// bound grows to `2 * input_len + input_len / 2`.
+    pub const fn max_encoded_len(&self, input_len: usize) -> usize {
+        let max = input_len.saturating_mul(2);
+        if self.split_camel_case {
+            max.saturating_add(input_len / 2)
+        } else {
+            max
+        }
+    }
+
+    /// Get the [`PunctuationMode`] for the given ascii non letter byte.
+    #[inline(always)]
+    pub(crate) const fn mode_for(&self, b: u8) -> PunctuationMode {
+        match b {
+            b'-' => self.hyphen,
+            b'\'' | b'`' => self.apostrophe,
+            _ if is_whitespace(b) => PunctuationMode::Break,
+            _ => self.other,
+        }
+    }
+}
+
+/// Check if `b` is an ascii whitespace character, these always break words.
+#[inline(always)]
+pub(crate) const fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r' | 0x0b | 0x0c)
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
@@ -0,0 +1,114 @@
+//! The separate stages of the encoding.
+//!
+//! The cologne phonetics are computed in three stages:
+//! 1. Every letter is coded according to its context, [`encode_raw`].
+//! 2. Repeated codes are collapsed into one, [`collapse`].
+//! 3. All `0` codes which are not at the start of a word are removed, [`remove_zeros`].
+//!
+//! The encoding functions and [`CologneVec::push`] apply all stages at once, for every input
+//! `remove_zeros(collapse(encode_raw(input)))` is equal to the encoded input.
+
+use crate::*;
+
+/// Encode the given utf8 bytes into the codes of every letter, without collapsing repeated
+/// codes or removing zeros.
+///
+/// Word breaks are [`CologneCode::Space`]s like in the encoded output, there are no breaks at the
+/// start or end, but repeated breaks are kept.
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{encode_raw, collapse, remove_zeros};
+/// let raw = encode_raw(b"Wikipedia Anna");
+/// assert_eq!(raw.to_string(), "304010200 0660");
+/// let collapsed = collapse(raw.as_slice());
+/// assert_eq!(collapsed.to_string(), "30401020 060");
+/// assert_eq!(remove_zeros(collapsed.as_slice()).to_string(), "3412 06");
+/// ```
+pub fn encode_raw(bytes: &[u8]) -> CologneVec {
+    encode_raw_with_options(bytes, &EncodeOptions::DEFAULT)
+}
+
+/// [`encode_raw`] using the given [`EncodeOptions`].
+pub fn encode_raw_with_options(bytes: &[u8], opts: &EncodeOptions) -> CologneVec {
+    let mut sink = RawSink(CologneVec::with_capacity(bytes.len()));
+    encode_into(bytes, opts, &mut sink);
+    sink.0
+}
+
+/// Collapse every run of equal codes into a single code, the second stage of the encoding.
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{collapse, CologneVec};
+/// let codes: CologneVec = "0066  60".parse().unwrap();
+/// assert_eq!(collapse(codes.as_slice()).to_string(), "06 60");
+/// ```
+pub fn collapse(codes: CologneSlice<'_>) -> CologneVec {
+    let mut out = CologneVec::with_capacity(codes.len());
+    for code in codes {
+        if out.last() != Some(code) {
+            out.push_raw(code);
+        }
+    }
+    out
+}
+
+/// Remove every `0` which is not the first code of a word, the third stage of the encoding.
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{remove_zeros, CologneVec};
+/// let codes: CologneVec = "0606 060".parse().unwrap();
+/// assert_eq!(remove_zeros(codes.as_slice()).to_string(), "066 06");
+/// ```
+pub fn remove_zeros(codes: CologneSlice<'_>) -> CologneVec {
+    let mut out = CologneVec::with_capacity(codes.len());
+    let mut prev = None;
+    for code in codes {
+        let leading = matches!(prev, None | Some(CologneCode::Space));
+        if code != CologneCode::Class0 || leading {
+            out.push_raw(code);
+        }
+        prev = Some(code);
+    }
+    out
+}
+
+/// [`CologneSink`] keeping every code, only word breaks at the start and end are dropped
+struct RawSink(CologneVec);
+
+impl CologneSink for RawSink {
+    fn push(&mut self, code: CologneCode) {
+        if code != CologneCode::Space || !self.0.is_empty() {
+            self.0.push_raw(code);
+        }
+    }
+
+    fn finish(&mut self) {
+        while self.0.last() == Some(CologneCode::Space) {
+            self.0.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::{option_sets, INPUTS};
+
+    #[test]
+    fn stages_are_encoding() {
+        for opts in option_sets() {
+            for input in INPUTS {
+                let raw = encode_raw_with_options(input.as_bytes(), &opts);
+                assert_ne!(raw.get(0), Some(CologneCode::Space));
+                assert_ne!(raw.last(), Some(CologneCode::Space));
+                let collapsed = collapse(raw.as_slice());
+                let mut vec = CologneVec::new();
+                vec.read_from_utf8_with_options(input.as_bytes(), &opts);
+                assert_eq!(remove_zeros(collapsed.as_slice()), vec, "{:?}", input);
+            }
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! [`serde`] support for [`CologneCode`] and [`CologneVec`].
+//!
+//! Human readable formats use the textual form: a single character for a [`CologneCode`] and
+//! the canonical string (e.g. `"657 52682"`) for a [`CologneVec`]. Binary formats use the
+//! nibble value of a [`CologneCode`] and a tuple of the len and the packed nibble bytes for a
+//! [`CologneVec`]. Deserialized data is always validated.
+
+use core::fmt;
+
+use alloc::vec::Vec;
+use serde::{
+    de::{self, Deserialize, Deserializer, SeqAccess, Visitor},
+    ser::{Serialize, SerializeTuple, Serializer},
+};
+
+use crate::*;
+
+impl Serialize for CologneCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_char(self.as_char())
+        } else {
+            serializer.serialize_u8(self.get())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for CologneCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_char(CodeVisitor)
+        } else {
+            deserializer.deserialize_u8(CodeVisitor)
+        }
+    }
+}
+
+/// Visitor for a single [`CologneCode`]
+struct CodeVisitor;
+
+impl Visitor<'_> for CodeVisitor {
+    type Value = CologneCode;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a cologne code character or nibble")
+    }
+
+    fn visit_char<E: de::Error>(self, v: char) -> Result<CologneCode, E> {
+        CologneCode::try_from(v).map_err(E::custom)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<CologneCode, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<CologneCode, E> {
+        u8::try_from(v)
+            .ok()
+            .and_then(CologneCode::from_nibble)
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+}
+
+impl Serialize for CologneSlice<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            let vec = self.to_vec();
+            let mut tup = serializer.serialize_tuple(2)?;
+            tup.serialize_element(&(vec.len() as u64))?;
+            tup.serialize_element(&PackedBytes(vec.get_raw()))?;
+            tup.end()
+        }
+    }
+}
+
+impl Serialize for CologneVec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            let mut tup = serializer.serialize_tuple(2)?;
+            tup.serialize_element(&(self.len() as u64))?;
+            tup.serialize_element(&PackedBytes(self.get_raw()))?;
+            tup.end()
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for CologneVec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(VecVisitor)
+        } else {
+            deserializer.deserialize_tuple(2, VecVisitor)
+        }
+    }
+}
+
+/// Visitor for a [`CologneVec`] either as string or as len and packed bytes
+struct VecVisitor;
+
+impl<'de> Visitor<'de> for VecVisitor {
+    type Value = CologneVec;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a cologne code string or a len and packed cologne codes")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<CologneVec, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<CologneVec, A::Error> {
+        let len: u64 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let PackedByteBuf(inner) = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let len = usize::try_from(len)
+            .map_err(|_| de::Error::invalid_value(de::Unexpected::Unsigned(len), &self))?;
+        CologneVec::try_from_raw(inner, len).map_err(de::Error::custom)
+    }
+}
+
+/// Serializes the packed bytes with [`Serializer::serialize_bytes`]
+struct PackedBytes<'a>(&'a [u8]);
+
+impl Serialize for PackedBytes<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Deserializes packed bytes from either bytes or a sequence of `u8`
+struct PackedByteBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for PackedByteBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_byte_buf(PackedBytesVisitor)
+    }
+}
+
+/// Visitor for [`PackedByteBuf`]
+struct PackedBytesVisitor;
+
+impl<'de> Visitor<'de> for PackedBytesVisitor {
+    type Value = PackedByteBuf;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("packed cologne code bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<PackedByteBuf, E> {
+        Ok(PackedByteBuf(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<PackedByteBuf, E> {
+        Ok(PackedByteBuf(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<PackedByteBuf, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(b) = seq.next_element()? {
+            bytes.push(b);
+        }
+        Ok(PackedByteBuf(bytes))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_test::{assert_de_tokens_error, assert_tokens, Configure, Token};
+
+    use super::*;
+
+    #[test]
+    fn code() {
+        assert_tokens(&CologneCode::Class7.readable(), &[Token::Char('7')]);
+        assert_tokens(&CologneCode::Space.readable(), &[Token::Char(' ')]);
+        assert_tokens(&CologneCode::Class7.compact(), &[Token::U8(7)]);
+        assert_tokens(&CologneCode::Space.compact(), &[Token::U8(14)]);
+        assert_de_tokens_error::<serde_test::Compact<CologneCode>>(
+            &[Token::U8(9)],
+            "invalid value: integer `9`, expected a cologne code character or nibble",
+        );
+        assert_de_tokens_error::<serde_test::Readable<CologneCode>>(
+            &[Token::Char('9')],
+            "invalid cologne code character '9' at index 0",
+        );
+    }
+
+    #[test]
+    fn vec() {
+        let mut outbuf = CologneVec::new();
+        outbuf.read_from_utf8("Müller-Lüdenscheidt".as_bytes());
+        assert_tokens(&outbuf.clone().readable(), &[Token::Str("657 52682")]);
+        assert_tokens(
+            &outbuf.clone().compact(),
+            &[
+                Token::Tuple { len: 2 },
+                Token::U64(9),
+                Token::Bytes(&[0x65, 0x7e, 0x52, 0x68, 0x20]),
+                Token::TupleEnd,
+            ],
+        );
+        assert_de_tokens_error::<serde_test::Compact<CologneVec>>(
+            &[
+                Token::Tuple { len: 2 },
+                Token::U64(3),
+                Token::Bytes(&[0x65, 0x71]),
+                Token::TupleEnd,
+            ],
+            "padding nibble is 0x1 instead of 0",
+        );
+        assert_de_tokens_error::<serde_test::Compact<CologneVec>>(
+            &[
+                Token::Tuple { len: 2 },
+                Token::U64(2),
+                Token::Bytes(&[0x6f]),
+                Token::TupleEnd,
+            ],
+            "invalid cologne code nibble 0xf at index 1",
+        );
+    }
+}
@@ -0,0 +1,282 @@
+//! Generic destinations for the encoder.
+
+use crate::*;
+
+/// A destination the encoder writes [`CologneCode`]s to.
+///
+/// [`push`](CologneSink::push) receives every code the letters of the input produce and has to
+/// apply the deduplication and zero removal rules like [`CologneVec::push`].
+/// [`finish`](CologneSink::finish) is called once after the whole input was read, like
+/// [`CologneVec::finish`].
+pub trait CologneSink {
+    /// Push a code according to the rules of how cologne codes have to be created.
+    fn push(&mut self, code: CologneCode);
+
+    /// Apply the rules on the last element after all codes were pushed.
+    fn finish(&mut self);
+}
+
+#[cfg(feature = "alloc")]
+impl CologneSink for CologneVec {
+    #[inline(always)]
+    fn push(&mut self, code: CologneCode) {
+        CologneVec::push(self, code)
+    }
+
+    fn finish(&mut self) {
+        CologneVec::finish(self)
+    }
+}
+
+/// Encode the given utf8 bytes into any [`CologneSink`] using the given [`EncodeOptions`].
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{encode_into, CologneArray, EncodeOptions};
+/// let mut buf = CologneArray::<4>::new();
+/// encode_into(b"Wikipedia", &EncodeOptions::DEFAULT, &mut buf);
+/// assert_eq!(buf.to_string(), "3412");
+/// ```
+pub fn encode_into<S: CologneSink + ?Sized>(bytes: &[u8], opts: &EncodeOptions, sink: &mut S) {
+    let mut encoder = Encoder::new();
+    for b in bytes {
+        encoder.read(*b, opts, sink);
+    }
+    encoder.finish(sink)
+}
+
+/// The state of the encoder between two input bytes.
+///
+/// Every loop over the input reads the bytes through an `Encoder`, the codes are pushed to a
+/// [`CologneSink`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Encoder {
+    /// Wether the previous byte started a two byte character
+    utf8: bool,
+    /// All values are interpreted as a normal alphabetic character and this maps to their
+    /// alphabet index, most ascii punctuation and whitespace characters are 26 and count as a
+    /// stop
+    last: [u8; 2],
+    /// Wether the previous character was uncertain and is not yet written
+    prev_uncertain: bool,
+    /// Wether the previous letter was a lowercase letter
+    prev_lower: bool,
+}
+
+impl Encoder {
+    /// Create an encoder at the start of the input
+    pub(crate) const fn new() -> Self {
+        Self {
+            utf8: false,
+            last: [Idx::SPACE, Idx::SPACE],
+            prev_uncertain: false,
+            prev_lower: false,
+        }
+    }
+
+    /// Read the next input byte and push its codes to `sink`. Returns the alphabetic index (see
+    /// [`Idx`]) the byte was read as, or `None` if the byte was skipped.
+    #[inline(always)]
+    pub(crate) fn read<S: CologneSink + ?Sized>(
+        &mut self,
+        b: u8,
+        opts: &EncodeOptions,
+        sink: &mut S,
+    ) -> Option<u8> {
+        let Self {
+            mut utf8,
+            mut last,
+            mut prev_uncertain,
+            mut prev_lower,
+        } = *self;
+        let idx = iter!(b, utf8, last, prev_uncertain, prev_lower, CologneSink::push, sink, opts);
+        *self = Self {
+            utf8,
+            last,
+            prev_uncertain,
+            prev_lower,
+        };
+        idx
+    }
+
+    /// Flush a pending uncertain character and finish `sink`, the trailing space is removed by
+    /// finish
+    pub(crate) fn finish<S: CologneSink + ?Sized>(self, sink: &mut S) {
+        let Self {
+            mut last,
+            mut prev_uncertain,
+            ..
+        } = self;
+        step!(Idx::SPACE, last, prev_uncertain, CologneSink::push, sink);
+        sink.finish()
+    }
+
+    /// Check if the last byte started a character which is not yet complete
+    #[cfg(feature = "alloc")]
+    pub(crate) const fn in_char(&self) -> bool {
+        self.utf8
+    }
+
+    /// Check if the code of the last character depends on the next one and is not yet written
+    #[cfg(feature = "alloc")]
+    pub(crate) const fn is_uncertain(&self) -> bool {
+        self.prev_uncertain
+    }
+
+    /// Get the alphabetic indices (see [`Idx`]) of the last two characters
+    #[cfg(feature = "alloc")]
+    pub(crate) const fn last(&self) -> [u8; 2] {
+        self.last
+    }
+}
+
+/// How a code is added to the codes before it, see [`PushRules::push`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Push {
+    /// The code is a word break at the start and is dropped
+    LeadingBreak,
+    /// The code is equal to the last code and is dropped
+    Duplicate,
+    /// The code is appended
+    Append,
+    /// The code replaces the last code, a `0` which is not at the start of a word
+    ReplaceZero,
+}
+
+/// The rules of how cologne codes have to be created, which only depend on the last two codes.
+///
+/// A word break at the start is dropped, a code equal to the last code is dropped and a `0`
+/// which is not at the start of a word is replaced by the next code. After all codes a trailing
+/// word break or `0` is removed. Every [`CologneSink`] of this crate which applies these rules
+/// decides through this type, it only maps the result to its storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PushRules {
+    /// The code before the last code and the last code
+    last: [Option<CologneCode>; 2],
+}
+
+impl PushRules {
+    /// Rules for the first code
+    pub(crate) const EMPTY: Self = Self::after(None, None);
+
+    /// Rules to continue codes which end with `before_last` and `last`
+    pub(crate) const fn after(before_last: Option<CologneCode>, last: Option<CologneCode>) -> Self {
+        Self {
+            last: [before_last, last],
+        }
+    }
+
+    /// Rules to continue after `codes`
+    pub(crate) fn after_slice(codes: CologneSlice<'_>) -> Self {
+        let before_last = codes.len().checked_sub(2).and_then(|i| codes.get(i));
+        Self::after(before_last, codes.last())
+    }
+
+    /// Decide how `code` is added and remember it as the last code
+    #[inline(always)]
+    pub(crate) fn push(&mut self, code: CologneCode) -> Push {
+        match self.last {
+            [_, None] if code == CologneCode::Space => Push::LeadingBreak,
+            [_, Some(last)] if last == code => Push::Duplicate,
+            [Some(before), Some(CologneCode::Class0)] if before != CologneCode::Space => {
+                self.last[1] = Some(code);
+                Push::ReplaceZero
+            }
+            [_, last] => {
+                self.last = [last, Some(code)];
+                Push::Append
+            }
+        }
+    }
+
+    /// Get the last code if it has to be removed after all codes were pushed, a word break or a
+    /// `0` which is not at the start of a word.
+    pub(crate) fn finish(&self) -> Option<CologneCode> {
+        match self.last {
+            [_, Some(CologneCode::Space)] => Some(CologneCode::Space),
+            [Some(before), Some(CologneCode::Class0)] if before != CologneCode::Space => {
+                Some(CologneCode::Class0)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the code before the last code and the last code
+    #[cfg(feature = "alloc")]
+    pub(crate) const fn last(&self) -> [Option<CologneCode>; 2] {
+        self.last
+    }
+}
+
+/// Storage of an [`IndexSink`] which stores the codes by their index.
+pub(crate) trait CodeBuffer {
+    /// Write `code` at the code index `idx`, which is at most the number of stored codes
+    fn write(&mut self, idx: usize, code: CologneCode);
+
+    /// Remove the last code, which is at the code index `idx`
+    fn remove(&mut self, idx: usize);
+}
+
+/// [`CologneSink`] applying the [`PushRules`] to the codes of a [`CodeBuffer`]
+pub(crate) struct IndexSink<B> {
+    /// The written codes
+    pub(crate) out: B,
+    /// Number of written codes
+    pub(crate) len: usize,
+    /// Rules for the next code
+    rules: PushRules,
+}
+
+impl<B: CodeBuffer> IndexSink<B> {
+    /// Create a new empty sink
+    pub(crate) const fn new(out: B) -> Self {
+        Self {
+            out,
+            len: 0,
+            rules: PushRules::EMPTY,
+        }
+    }
+}
+
+impl<B: CodeBuffer> CologneSink for IndexSink<B> {
+    fn push(&mut self, code: CologneCode) {
+        match self.rules.push(code) {
+            Push::Append => {
+                self.out.write(self.len, code);
+                self.len += 1;
+            }
+            Push::ReplaceZero => self.out.write(self.len - 1, code),
+            Push::LeadingBreak | Push::Duplicate => (),
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.rules.finish().is_some() {
+            self.len -= 1;
+            self.out.remove(self.len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn push_rules() {
+        use CologneCode::*;
+        let mut rules = PushRules::EMPTY;
+        assert_eq!(rules.push(Space), Push::LeadingBreak);
+        assert_eq!(rules.push(Class0), Push::Append);
+        assert_eq!(rules.finish(), None);
+        assert_eq!(rules.push(Class0), Push::Duplicate);
+        assert_eq!(rules.push(Class6), Push::Append);
+        assert_eq!(rules.push(Class0), Push::Append);
+        assert_eq!(rules.finish(), Some(Class0));
+        assert_eq!(rules.push(Space), Push::ReplaceZero);
+        assert_eq!(rules.finish(), Some(Space));
+        assert_eq!(rules.push(Class0), Push::Append);
+        assert_eq!(rules.push(Class7), Push::Append);
+        assert_eq!(PushRules::after(Some(Space), Some(Class0)).finish(), None);
+    }
+}
@@ -0,0 +1,572 @@
+//! Borrowed views into packed [`CologneCode`]s.
+
+use core::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    ops::{Bound, RangeBounds},
+};
+
+use crate::{code_iter::nibble_at, *};
+
+/// Check that `inner` holds exactly `len` valid codes and that the padding nibble of an odd
+/// `len` is zero.
+pub(crate) fn validate_packed(inner: &[u8], len: usize) -> Result<(), InvalidCode> {
+    if inner.len() != len.div_ceil(2) {
+        return Err(InvalidCode::Length {
+            len,
+            bytes: inner.len(),
+        });
+    }
+    for (i, b) in inner.iter().enumerate() {
+        let hi = *b >> 4;
+        if CologneCode::from_nibble(hi).is_none() {
+            return Err(InvalidCode::Nibble {
+                index: i << 1,
+                value: hi,
+            });
+        }
+        let lo = *b & 0x0f;
+        let lo_idx = (i << 1) | 0x01;
+        if lo_idx == len {
+            if lo != 0 {
+                return Err(InvalidCode::Padding { value: lo });
+            }
+        } else if CologneCode::from_nibble(lo).is_none() {
+            return Err(InvalidCode::Nibble {
+                index: lo_idx,
+                value: lo,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Resolve `range` to a `start..end` pair of indices into a sequence of `len` codes or `None`
+/// if it is out of bounds.
+pub(crate) fn resolve_range(range: &impl RangeBounds<usize>, len: usize) -> Option<(usize, usize)> {
+    let start = match range.start_bound() {
+        Bound::Included(s) => *s,
+        Bound::Excluded(s) => s.checked_add(1)?,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(e) => e.checked_add(1)?,
+        Bound::Excluded(e) => *e,
+        Bound::Unbounded => len,
+    };
+    if start > end || end > len {
+        None
+    } else {
+        Some((start, end))
+    }
+}
+
+/// A borrowed view into a sequence of packed [`CologneCode`]s, `CologneSlice` relates to
+/// [`CologneVec`] like `&str` relates to `String`.
+///
+/// As two codes are stored in one byte a `CologneSlice` can start and end at any nibble,
+/// which can't be represented by the `&[u8]` returned from [`CologneVec::get_raw`].
+///
+/// Equality and ordering compare the codes lexicographically like slices do.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// # use cologne_phonetics::{CologneVec, CologneCode};
+/// let mut buf = CologneVec::new();
+/// buf.read_from_utf8(b"Marius Macher");
+/// let slice = buf.as_slice();
+/// assert_eq!(slice.len(), 7);
+/// assert_eq!(slice.get(4), Some(CologneCode::Class6));
+/// assert_eq!(slice.slice(1..3), CologneVec::from_codes(&[
+///     CologneCode::Class7,
+///     CologneCode::Class8,
+/// ]));
+/// assert!(slice.starts_with(slice.slice(..3)));
+/// # }
+/// ```
+#[derive(Clone, Copy)]
+pub struct CologneSlice<'a> {
+    /// The bytes containing the codes, the first code is stored in the first byte
+    bytes: &'a [u8],
+    /// Nibble offset of the first code in `bytes`, either 0 or 1
+    start: usize,
+    /// Number of codes in this slice
+    len: usize,
+}
+
+impl<'a> CologneSlice<'a> {
+    /// Create a new `CologneSlice` from raw parts.
+    ///
+    /// # Safety
+    /// The nibbles `start..start + len` of `bytes` must be valid [`CologneCode`]s.
+    pub(crate) unsafe fn from_raw_parts(bytes: &'a [u8], start: usize, len: usize) -> Self {
+        let first = start >> 1;
+        Self {
+            bytes: bytes.get_unchecked(first..),
+            start: start & 0x01,
+            len,
+        }
+    }
+
+    /// Create an empty `CologneSlice`
+    pub const fn empty() -> Self {
+        Self {
+            bytes: &[],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    /// Get the number of [`CologneCode`]s in this slice
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if this slice contains no [`CologneCode`]s
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the [`CologneCode`] at `index` or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<CologneCode> {
+        if index < self.len {
+            // SAFETY: index is inside of the slice
+            Some(unsafe { nibble_at(self.bytes, self.start.wrapping_add(index)) })
+        } else {
+            None
+        }
+    }
+
+    /// Get the first [`CologneCode`]
+    pub fn first(&self) -> Option<CologneCode> {
+        self.get(0)
+    }
+
+    /// Get the last [`CologneCode`]
+    pub fn last(&self) -> Option<CologneCode> {
+        self.get(self.len.wrapping_sub(1))
+    }
+
+    /// Get a sub slice for the given range of code indices or `None` if the range is out of
+    /// bounds. The range can start and end at any code, not only at byte borders.
+    pub fn get_slice(&self, range: impl RangeBounds<usize>) -> Option<CologneSlice<'a>> {
+        let (start, end) = resolve_range(&range, self.len)?;
+        // SAFETY: start..end is inside of this slice
+        let len = end.wrapping_sub(start);
+        Some(unsafe { Self::from_raw_parts(self.bytes, self.start.wrapping_add(start), len) })
+    }
+
+    /// Get a sub slice for the given range of code indices.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds, see [`get_slice`](Self::get_slice) for a non
+    /// panicking version.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> CologneSlice<'a> {
+        match self.get_slice(range) {
+            Some(slice) => slice,
+            None => panic!("range out of bounds for CologneSlice of len {}", self.len),
+        }
+    }
+
+    /// Split this slice into two at `mid`, the first slice contains the codes `0..mid`.
+    ///
+    /// # Panics
+    /// Panics if `mid > len`.
+    pub fn split_at(&self, mid: usize) -> (CologneSlice<'a>, CologneSlice<'a>) {
+        (self.slice(..mid), self.slice(mid..))
+    }
+
+    /// Check if `prefix` is a prefix of this slice
+    pub fn starts_with(&self, prefix: CologneSlice<'_>) -> bool {
+        self.get_slice(..prefix.len)
+            .is_some_and(|start| start == prefix)
+    }
+
+    /// Check if `suffix` is a suffix of this slice
+    pub fn ends_with(&self, suffix: CologneSlice<'_>) -> bool {
+        self.len
+            .checked_sub(suffix.len)
+            .is_some_and(|start| self.slice(start..) == suffix)
+    }
+
+    /// Get the index of the first occurrence of `code`.
+    pub fn position(&self, code: CologneCode) -> Option<usize> {
+        let (bytes, end) = self.packed_bytes();
+        let pattern = code.get() * 0x11;
+        for (i, b) in bytes.iter().enumerate() {
+            let diff = *b ^ pattern;
+            let hi = i << 1;
+            if diff & 0xf0 == 0 && hi >= self.start && hi < end {
+                return Some(hi.wrapping_sub(self.start));
+            }
+            let lo = hi | 0x01;
+            if diff & 0x0f == 0 && lo < end {
+                return Some(lo.wrapping_sub(self.start));
+            }
+        }
+        None
+    }
+
+    /// Get the index of the last occurrence of `code`.
+    pub fn rposition(&self, code: CologneCode) -> Option<usize> {
+        let (bytes, end) = self.packed_bytes();
+        let pattern = code.get() * 0x11;
+        for (i, b) in bytes.iter().enumerate().rev() {
+            let diff = *b ^ pattern;
+            let lo = (i << 1) | 0x01;
+            if diff & 0x0f == 0 && lo < end {
+                return Some(lo.wrapping_sub(self.start));
+            }
+            let hi = i << 1;
+            if diff & 0xf0 == 0 && hi >= self.start && hi < end {
+                return Some(hi.wrapping_sub(self.start));
+            }
+        }
+        None
+    }
+
+    /// Count the occurrences of `code`.
+    pub fn count(&self, code: CologneCode) -> usize {
+        let (bytes, end) = self.packed_bytes();
+        let pattern = code.get() * 0x11;
+        let mut count = 0usize;
+        for (i, b) in bytes.iter().enumerate() {
+            let diff = *b ^ pattern;
+            let hi = i << 1;
+            if diff & 0xf0 == 0 && hi >= self.start && hi < end {
+                count = count.wrapping_add(1);
+            }
+            if diff & 0x0f == 0 && (hi | 0x01) < end {
+                count = count.wrapping_add(1);
+            }
+        }
+        count
+    }
+
+    /// Get the bytes containing the codes of this slice and the nibble index one past the last
+    /// code in these bytes.
+    fn packed_bytes(&self) -> (&'a [u8], usize) {
+        let end = self.start.wrapping_add(self.len);
+        let byte_len = end.wrapping_add(1) >> 1;
+        (self.bytes.get(..byte_len).unwrap_or_default(), end)
+    }
+
+    /// Iterate the words of this slice, which are separated by [`CologneCode::Space`].
+    ///
+    /// Like [`str::split`] empty words are yielded between consecutive spaces, but an empty
+    /// slice has no words at all.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// # use cologne_phonetics::CologneVec;
+    /// let mut buf = CologneVec::new();
+    /// buf.read_from_utf8("Müller-Lüdenscheidt".as_bytes());
+    /// let words: Vec<_> = buf.words().map(|w| format!("{:?}", w)).collect();
+    /// assert_eq!(words, ["[657]", "[52682]"]);
+    /// # }
+    /// ```
+    pub fn words(&self) -> Words<'a> {
+        Words {
+            rest: *self,
+            done: self.is_empty(),
+        }
+    }
+
+    /// Get the number of words in this slice, see [`words`](Self::words).
+    pub fn word_count(&self) -> usize {
+        if self.is_empty() {
+            0
+        } else {
+            self.count(CologneCode::Space).wrapping_add(1)
+        }
+    }
+
+    /// Get the `n`th word of this slice, see [`words`](Self::words).
+    pub fn nth_word(&self, n: usize) -> Option<CologneSlice<'a>> {
+        self.words().nth(n)
+    }
+
+    /// Iterate all [`CologneCode`]s of this slice
+    pub fn iter(&self) -> Iter<'a> {
+        // SAFETY: All nibbles in start..start + len are valid codes
+        unsafe { Iter::new(self.bytes, self.start, self.start.wrapping_add(self.len)) }
+    }
+
+    /// Display the codes of this slice in the given [`CologneFormat`].
+    pub fn display_with<'s>(&self, format: CologneFormat<'s>) -> FormattedCodes<'a, 's> {
+        format.display(*self)
+    }
+
+    /// Iterate the codes of this slice packed two per byte like in a [`CologneVec`], the
+    /// padding nibble of an odd len is `0`.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn packed_iter(&self) -> impl Iterator<Item = u8> + 'a {
+        let slice = *self;
+        (0..self.len.div_ceil(2)).map(move |i| {
+            let hi = slice.get(i << 1).map_or(0, CologneCode::get);
+            let lo = slice.get((i << 1) | 0x01).map_or(0, CologneCode::get);
+            (hi << 4) | lo
+        })
+    }
+
+    /// Copy the codes of this slice into a new [`CologneVec`].
+    #[cfg(feature = "alloc")]
+    pub fn to_vec(&self) -> CologneVec {
+        if self.start == 0 {
+            let byte_len = self.len.wrapping_add(1) >> 1;
+            let bytes = self.bytes.get(..byte_len).unwrap_or_default();
+            // SAFETY: The first len nibbles are valid codes
+            unsafe { CologneVec::from_packed(bytes, self.len) }
+        } else {
+            let mut vec = CologneVec::with_capacity(self.len.wrapping_add(1) >> 1);
+            for code in self.iter() {
+                vec.push_raw(code);
+            }
+            vec
+        }
+    }
+}
+
+/// Iterator over the words of a [`CologneSlice`], created by [`CologneSlice::words`] or
+/// [`CologneVec::words`].
+#[derive(Debug, Clone)]
+pub struct Words<'a> {
+    /// The not yet yielded part of the slice
+    rest: CologneSlice<'a>,
+    /// Wether all words were yielded
+    done: bool,
+}
+
+impl<'a> Iterator for Words<'a> {
+    type Item = CologneSlice<'a>;
+
+    fn next(&mut self) -> Option<CologneSlice<'a>> {
+        if self.done {
+            return None;
+        }
+        match self.rest.position(CologneCode::Space) {
+            Some(idx) => {
+                let word = self.rest.slice(..idx);
+                self.rest = self.rest.slice(idx.wrapping_add(1)..);
+                Some(word)
+            }
+            None => {
+                self.done = true;
+                Some(self.rest)
+            }
+        }
+    }
+}
+
+impl<'a> DoubleEndedIterator for Words<'a> {
+    fn next_back(&mut self) -> Option<CologneSlice<'a>> {
+        if self.done {
+            return None;
+        }
+        match self.rest.rposition(CologneCode::Space) {
+            Some(idx) => {
+                let word = self.rest.slice(idx.wrapping_add(1)..);
+                self.rest = self.rest.slice(..idx);
+                Some(word)
+            }
+            None => {
+                self.done = true;
+                Some(self.rest)
+            }
+        }
+    }
+}
+
+impl core::iter::FusedIterator for Words<'_> {}
+
+impl Default for CologneSlice<'_> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<'a> IntoIterator for CologneSlice<'a> {
+    type Item = CologneCode;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl PartialEq for CologneSlice<'_> {
+    fn eq(&self, other: &CologneSlice<'_>) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for CologneSlice<'_> {}
+
+impl PartialOrd for CologneSlice<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CologneSlice<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl Hash for CologneSlice<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        for code in self.iter() {
+            code.hash(state);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq<CologneVec> for CologneSlice<'_> {
+    fn eq(&self, other: &CologneVec) -> bool {
+        *self == other.as_slice()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq<CologneSlice<'_>> for CologneVec {
+    fn eq(&self, other: &CologneSlice<'_>) -> bool {
+        self.as_slice() == *other
+    }
+}
+
+/// Writes the canonical form, e.g. `"657 52682"`.
+impl core::fmt::Display for CologneSlice<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for code in self.iter() {
+            core::fmt::Write::write_char(f, code.as_char())?;
+        }
+        Ok(())
+    }
+}
+
+impl core::fmt::Debug for CologneSlice<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "[")?;
+        for code in self.iter() {
+            write!(f, "{}", code)?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::*;
+
+    /// Encode `s` into a `Vec<CologneCode>` and a `CologneVec`
+    fn encoded(s: &str) -> (Vec<CologneCode>, CologneVec) {
+        let mut codes = Vec::new();
+        utf8_to_cologne_phonetics_vec(s.as_bytes(), &mut codes);
+        let mut vec = CologneVec::new();
+        vec.read_from_utf8(s.as_bytes());
+        (codes, vec)
+    }
+
+    #[test]
+    fn get() {
+        let (codes, vec) = encoded("Anhand von Grundlagen");
+        let slice = vec.as_slice();
+        assert_eq!(slice.len(), codes.len());
+        for (i, code) in codes.iter().enumerate() {
+            assert_eq!(slice.get(i), Some(*code));
+        }
+        assert_eq!(slice.get(codes.len()), None);
+        assert_eq!(slice.first(), codes.first().copied());
+        assert_eq!(slice.last(), codes.last().copied());
+    }
+
+    #[test]
+    fn all_ranges() {
+        let (codes, vec) = encoded("Er kam, Er sah, Er siegte");
+        let slice = vec.as_slice();
+        for start in 0..=codes.len() {
+            for end in start..=codes.len() {
+                let sub = slice.slice(start..end);
+                let expected = &codes[start..end];
+                assert_eq!(sub.len(), expected.len());
+                assert!(sub.iter().eq(expected.iter().copied()));
+                assert!(sub.iter().rev().eq(expected.iter().rev().copied()));
+                assert!(sub.to_vec().iter().eq(expected.iter().copied()));
+                assert_eq!(sub.to_vec().as_slice(), sub);
+                for i in 0..expected.len() {
+                    let inner = sub.slice(i..);
+                    assert!(inner.iter().eq(expected[i..].iter().copied()));
+                }
+            }
+        }
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = slice.get_slice(3..2);
+        assert_eq!(reversed, None);
+        assert_eq!(slice.get_slice(..=codes.len()), None);
+        assert_eq!(slice.get_slice(..), Some(slice));
+    }
+
+    #[test]
+    fn ordering() {
+        let (_, a) = encoded("Meier Aal");
+        let (_, b) = encoded("Meier Bach");
+        let (_, c) = encoded("Meier");
+        let a = a.as_slice();
+        let b = b.as_slice();
+        let c = c.as_slice();
+        assert!(a < b);
+        assert!(c < a);
+        assert!(a.starts_with(c));
+        assert!(!c.starts_with(a));
+        assert!(a.slice(1..) > a.slice(..1));
+        assert_eq!(a.slice(..2), b.slice(..2));
+        assert!(b.ends_with(b.slice(3..)));
+        let (head, tail) = b.split_at(2);
+        assert_eq!(head.len() + tail.len(), b.len());
+        assert!(head.iter().chain(tail).eq(b.iter()));
+    }
+
+    #[test]
+    fn words() {
+        let (codes, vec) = encoded("Er kam, Er sah, Er siegte");
+        let expected: Vec<&[CologneCode]> = codes.split(|c| *c == CologneCode::Space).collect();
+        let slice = vec.as_slice();
+        assert_eq!(slice.word_count(), expected.len());
+        assert_eq!(slice.words().count(), expected.len());
+        for (i, (word, exp)) in slice.words().zip(&expected).enumerate() {
+            assert!(word.iter().eq(exp.iter().copied()));
+            assert_eq!(slice.nth_word(i), Some(word));
+        }
+        assert!(slice.words().rev().eq(slice.words().collect::<Vec<_>>().into_iter().rev()));
+        assert_eq!(slice.nth_word(expected.len()), None);
+
+        // Words of odd aligned sub slices
+        for start in 0..codes.len() {
+            let sub = slice.slice(start..);
+            let expected: Vec<&[CologneCode]> =
+                codes[start..].split(|c| *c == CologneCode::Space).collect();
+            assert_eq!(sub.word_count(), expected.len());
+            assert!(sub.words().map(|w| w.len()).eq(expected.iter().map(|w| w.len())));
+            assert_eq!(
+                sub.position(CologneCode::Space),
+                codes[start..].iter().position(|c| *c == CologneCode::Space)
+            );
+            assert_eq!(
+                sub.rposition(CologneCode::Space),
+                codes[start..].iter().rposition(|c| *c == CologneCode::Space)
+            );
+        }
+
+        let empty = CologneVec::new();
+        assert_eq!(empty.word_count(), 0);
+        assert_eq!(empty.words().next(), None);
+        let (_, single) = encoded("Breschnew");
+        assert_eq!(single.word_count(), 1);
+        assert_eq!(single.nth_word(0), Some(single.as_slice()));
+    }
+}
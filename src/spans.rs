@@ -0,0 +1,376 @@
+//! Mapping of encoded [`CologneCode`]s back to the input bytes which produced them.
+
+use core::{fmt, ops::Range};
+
+use alloc::vec::Vec;
+
+use crate::{
+    options::is_whitespace,
+    sink::{Encoder, Push, PushRules},
+    *,
+};
+
+/// [`CologneCode`]s together with the range of input bytes which produced every code.
+///
+/// The spans are aligned with the codes, `spans()[i]` are the bytes of the letters which
+/// produced `codes().get(i)`:
+/// - Both codes of an `X` have the span of the `X`.
+/// - A code which is merged with an equal previous code by deduplication extends the span of
+///   that code, e.g. both `l` of `"ll"` map to the single `5`.
+/// - A code of `C`, `D`, `T` or `P` has the span of that letter, even though it depends on the
+///   following letter.
+/// - A [`CologneCode::Space`] has the span of the breaking character, for a split camel case
+///   word that is the uppercase letter.
+/// - A non leading `0` which is replaced by the following code is dropped, so the span of the
+///   vowel is not part of any code.
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{SpannedCodes, EncodeOptions};
+/// let spanned = SpannedCodes::encode("Müller".as_bytes(), &EncodeOptions::DEFAULT);
+/// assert_eq!(spanned.codes().to_string(), "657");
+/// assert_eq!(spanned.spans(), [0..1, 3..5, 6..7]);
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct SpannedCodes {
+    /// The encoded codes
+    codes: CologneVec,
+    /// The source span of every code
+    spans: Vec<Range<usize>>,
+}
+
+impl SpannedCodes {
+    /// Encode the given utf8 bytes and record the source span of every code.
+    pub fn encode(bytes: &[u8], opts: &EncodeOptions) -> Self {
+        let mut sink = SpanSink {
+            out: Self::default(),
+            rules: PushRules::EMPTY,
+            current: 0..0,
+            flush: None,
+        };
+        let mut encoder = Encoder::new();
+        // Start of the current character, umlauts are two bytes long
+        let mut start = 0;
+        // Span of the uncertain character which is not yet written
+        let mut uncertain = 0..0;
+
+        for (i, b) in bytes.iter().enumerate() {
+            if !encoder.in_char() {
+                start = i;
+            }
+            sink.current = start..i + 1;
+            // A pending uncertain character is always written first
+            sink.flush = encoder.is_uncertain().then(|| uncertain.clone());
+            encoder.read(*b, opts, &mut sink);
+            if encoder.is_uncertain() {
+                uncertain = start..i + 1;
+            }
+        }
+
+        sink.current = bytes.len()..bytes.len();
+        sink.flush = encoder.is_uncertain().then_some(uncertain);
+        encoder.finish(&mut sink);
+        sink.out
+    }
+
+    /// Get the encoded codes
+    pub fn codes(&self) -> &CologneVec {
+        &self.codes
+    }
+
+    /// Get the source spans, aligned with [`codes`](Self::codes)
+    pub fn spans(&self) -> &[Range<usize>] {
+        &self.spans
+    }
+
+    /// Get the number of codes
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    /// Check if no codes were encoded
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    /// Get the code at `index` and its source span or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<(CologneCode, Range<usize>)> {
+        Some((self.codes.get(index)?, self.spans.get(index)?.clone()))
+    }
+
+    /// Iterate all codes with their source spans
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (CologneCode, Range<usize>)> + '_ {
+        self.codes.iter().zip(self.spans.iter().cloned())
+    }
+
+    /// Split into the codes and the spans
+    pub fn into_parts(self) -> (CologneVec, Vec<Range<usize>>) {
+        (self.codes, self.spans)
+    }
+}
+
+impl fmt::Debug for SpannedCodes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(|(code, span)| (code.as_char(), span)))
+            .finish()
+    }
+}
+
+/// [`CologneSink`] recording the source span of every code
+struct SpanSink {
+    /// The codes and spans written so far
+    out: SpannedCodes,
+    /// Rules for the next code
+    rules: PushRules,
+    /// Span of the character which is currently read
+    current: Range<usize>,
+    /// Span of the uncertain character which is written by the next push
+    flush: Option<Range<usize>>,
+}
+
+impl CologneSink for SpanSink {
+    fn push(&mut self, code: CologneCode) {
+        let span = self.flush.take().unwrap_or_else(|| self.current.clone());
+        match self.rules.push(code) {
+            Push::Append => {
+                self.out.codes.push_raw(code);
+                self.out.spans.push(span);
+            }
+            Push::ReplaceZero => {
+                self.out.codes.replace_last(code);
+                if let Some(last) = self.out.spans.last_mut() {
+                    *last = span;
+                }
+            }
+            Push::Duplicate => {
+                // Deduplicated codes are merged into the previous code
+                if let Some(last) = self.out.spans.last_mut() {
+                    last.start = last.start.min(span.start);
+                    last.end = last.end.max(span.end);
+                }
+            }
+            Push::LeadingBreak => (),
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.rules.finish().is_some() {
+            self.out.codes.pop();
+            self.out.spans.pop();
+        }
+    }
+}
+
+/// Encode every word of `input` on its own, returns the source span, the original text and
+/// the codes of every word in input order.
+///
+/// The words are the parts the encoder separates with [`CologneCode::Space`]: runs of
+/// characters between whitespace, breaking punctuation (see [`PunctuationMode::Break`]) and,
+/// if enabled, camel case boundaries. Joining and ignored characters stay part of their word.
+/// Words which encode to no code at all (e.g. `"h"`) are reported with empty codes, a
+/// whitespace separated token made only of breaking punctuation is reported as one such word.
+///
+/// The non empty codes joined by spaces are equal to encoding the whole input.
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{encode_words, EncodeOptions};
+/// let words = encode_words("Müller-Lüdenscheidt , h", &EncodeOptions::DEFAULT);
+/// let words: Vec<_> = words
+///     .iter()
+///     .map(|(span, word, codes)| (span.clone(), *word, codes.to_string()))
+///     .collect();
+/// assert_eq!(words, [
+///     (0..7, "Müller", "657".to_string()),
+///     (8..21, "Lüdenscheidt", "52682".to_string()),
+///     (22..23, ",", "".to_string()),
+///     (24..25, "h", "".to_string()),
+/// ]);
+/// ```
+pub fn encode_words<'a>(
+    input: &'a str,
+    opts: &EncodeOptions,
+) -> Vec<(Range<usize>, &'a str, CologneVec)> {
+    let mut sink = WordSink {
+        input,
+        words: Vec::new(),
+        word: None,
+        codes: CologneVec::new(),
+        broke: false,
+    };
+    let mut encoder = Encoder::new();
+    // Start of the current whitespace separated token and wether it contains a word
+    let mut token: Option<(usize, bool)> = None;
+
+    for (i, c) in input.char_indices() {
+        let span = i..i + c.len_utf8();
+        sink.broke = false;
+        for b in &input.as_bytes()[span.clone()] {
+            encoder.read(*b, opts, &mut sink);
+        }
+        // A breaking character leaves the encoder at a word break, after a camel case break
+        // the character starts the next word
+        let breaks = sink.broke && encoder.last()[1] == Idx::SPACE;
+        if !breaks {
+            sink.word.get_or_insert(span.clone()).end = span.end;
+        }
+
+        if u8::try_from(c).is_ok_and(is_whitespace) {
+            if let Some((start, false)) = token.take() {
+                sink.words.push((start..i, &input[start..i], CologneVec::new()));
+            }
+        } else {
+            token.get_or_insert((i, false)).1 |= !breaks;
+        }
+    }
+
+    encoder.finish(&mut sink);
+    if let Some((start, false)) = token {
+        sink.words
+            .push((start..input.len(), &input[start..], CologneVec::new()));
+    }
+    sink.words
+}
+
+/// [`CologneSink`] splitting the codes into words at the word breaks the encoder pushes
+struct WordSink<'a> {
+    /// The encoded input
+    input: &'a str,
+    /// All complete words
+    words: Vec<(Range<usize>, &'a str, CologneVec)>,
+    /// Span of the current word
+    word: Option<Range<usize>>,
+    /// Codes of the current word
+    codes: CologneVec,
+    /// Wether a word break was pushed while reading the current character
+    broke: bool,
+}
+
+impl CologneSink for WordSink<'_> {
+    fn push(&mut self, code: CologneCode) {
+        if code != CologneCode::Space {
+            self.codes.push(code);
+            return;
+        }
+        self.broke = true;
+        let mut codes = core::mem::take(&mut self.codes);
+        if let Some(span) = self.word.take() {
+            codes.finish();
+            self.words.push((span.clone(), &self.input[span], codes));
+        }
+    }
+
+    fn finish(&mut self) {}
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::{String, ToString};
+
+    use super::*;
+    use crate::tests::{option_sets, INPUTS};
+
+    fn check(input: &str, codes: &str, spans: &[Range<usize>]) {
+        let spanned = SpannedCodes::encode(input.as_bytes(), &EncodeOptions::DEFAULT);
+        assert_eq!(spanned.codes().to_string(), codes, "{:?}", input);
+        assert_eq!(spanned.spans(), spans, "{:?}", input);
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn spans_of_rules() {
+        check("", "", &[]);
+        check("a", "0", &[0..1]);
+        // Uncertain characters are written later with their own span
+        check("Pat", "12", &[0..1, 2..3]);
+        check("Philipp", "351", &[0..1, 3..4, 5..7]);
+        // Both codes of X
+        check("Xaver", "4837", &[0..1, 0..1, 2..3, 4..5]);
+        check("Hexe", "048", &[1..2, 2..3, 2..3]);
+        // Dedup merges and leading zeros
+        check("Meier", "67", &[0..1, 4..5]);
+        check("Anna", "06", &[0..1, 1..3]);
+        // Umlauts are two bytes
+        check(
+            "Müller-Lüdenscheidt",
+            "657 52682",
+            &[0..1, 3..5, 6..7, 7..8, 8..9, 11..12, 13..14, 14..16, 19..21],
+        );
+    }
+
+    #[test]
+    fn same_as_vec() {
+        for opts in option_sets() {
+            for input in INPUTS {
+                let spanned = SpannedCodes::encode(input.as_bytes(), &opts);
+                let mut vec = CologneVec::new();
+                vec.read_from_utf8_with_options(input.as_bytes(), &opts);
+                assert_eq!(spanned.codes(), &vec);
+                assert_eq!(spanned.spans().len(), vec.len());
+                for w in spanned.spans().windows(2) {
+                    assert!(w[0].start <= w[1].start, "{:?}", input);
+                }
+                for (code, span) in spanned.iter() {
+                    assert!(span.end <= input.len());
+                    let src = &input[span.clone()];
+                    if code != CologneCode::Space {
+                        assert!(src.chars().all(char::is_alphabetic), "{:?}", src);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn words_match_codes() {
+        for opts in option_sets() {
+            for input in INPUTS {
+                let words = encode_words(input, &opts);
+                let mut joined = CologneVec::new();
+                for (span, word, codes) in &words {
+                    assert_eq!(&input[span.clone()], *word);
+                    assert!(!word.is_empty() && !word.contains(char::is_whitespace));
+                    joined.append(codes, Some(CologneCode::Space));
+                }
+                for w in words.windows(2) {
+                    assert!(w[0].0.end <= w[1].0.start, "{:?}", input);
+                }
+                let mut vec = CologneVec::new();
+                vec.read_from_utf8_with_options(input.as_bytes(), &opts);
+                assert_eq!(joined, vec, "{:?}", input);
+            }
+        }
+
+        let camel = EncodeOptions::new().split_camel_case(true);
+        let words = encode_words("MaxMustermann h !!", &camel);
+        let words: Vec<_> = words
+            .iter()
+            .map(|(span, word, codes)| (span.clone(), *word, codes.to_string()))
+            .collect();
+        assert_eq!(
+            words,
+            [
+                (0..3, "Max", "648".to_string()),
+                (3..13, "Mustermann", "682766".to_string()),
+                (14..15, "h", "".to_string()),
+                (16..18, "!!", "".to_string()),
+            ]
+        );
+
+        let words = |input: &str, opts: &EncodeOptions| -> Vec<(Range<usize>, String)> {
+            let words = encode_words(input, opts);
+            words.into_iter().map(|(span, _, codes)| (span, codes.to_string())).collect()
+        };
+        // The code of the T depends on the next character but belongs to its word
+        assert_eq!(
+            words("PatBad", &camel),
+            [(0..3, "12".to_string()), (3..6, "12".to_string())]
+        );
+        let ignore = EncodeOptions::new().apostrophe(PunctuationMode::Ignore);
+        assert_eq!(
+            words("'- h'-", &ignore),
+            [(0..1, "".to_string()), (3..5, "".to_string())]
+        );
+    }
+}
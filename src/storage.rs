@@ -0,0 +1,244 @@
+//! Backing storage of a [`CologneVec`] which keeps short codes inline.
+
+use core::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    ops::{Deref, DerefMut},
+};
+
+use alloc::vec::Vec;
+
+/// Number of bytes, so twice the number of codes, stored inline before spilling to the heap.
+/// With this capacity a [`Storage`] is not larger than a `Vec<u8>`.
+pub(crate) const INLINE_CAP: usize = 11;
+
+/// Byte storage which is stored inline up to [`INLINE_CAP`] bytes and on the heap beyond.
+///
+/// Behaves like a `Vec<u8>`, all comparisons and hashes only depend on the stored bytes. Once
+/// spilled to the heap the storage stays there to reuse the allocation.
+pub(crate) enum Storage {
+    /// The bytes `..len` of `buf` are stored
+    Inline {
+        /// Number of stored bytes
+        len: u8,
+        /// The inline buffer
+        buf: [u8; INLINE_CAP],
+    },
+    /// Bytes stored on the heap
+    Heap(Vec<u8>),
+}
+
+impl Storage {
+    /// Create a new empty inline storage
+    pub(crate) const fn new() -> Self {
+        Self::Inline {
+            len: 0,
+            buf: [0; INLINE_CAP],
+        }
+    }
+
+    /// Create a new empty storage for at least `cap` bytes, only allocates if `cap` doesn't fit
+    /// inline.
+    pub(crate) fn with_capacity(cap: usize) -> Self {
+        if cap <= INLINE_CAP {
+            Self::new()
+        } else {
+            Self::Heap(Vec::with_capacity(cap))
+        }
+    }
+
+    /// Check if the bytes are stored inline
+    pub(crate) fn is_inline(&self) -> bool {
+        matches!(self, Self::Inline { .. })
+    }
+
+    /// Convert into a `Vec<u8>`, only allocates if the bytes are stored inline.
+    pub(crate) fn into_vec(self) -> Vec<u8> {
+        match self {
+            Self::Inline { len, buf } => buf[..usize::from(len)].to_vec(),
+            Self::Heap(vec) => vec,
+        }
+    }
+
+    /// Move inline bytes to the heap with space for at least `additional` more bytes, returns
+    /// the heap storage.
+    fn spill(&mut self, additional: usize) -> &mut Vec<u8> {
+        if let Self::Inline { len, buf } = self {
+            let bytes = &buf[..usize::from(*len)];
+            let mut vec = Vec::with_capacity((bytes.len() + additional).max(INLINE_CAP * 2));
+            vec.extend_from_slice(bytes);
+            *self = Self::Heap(vec);
+        }
+        match self {
+            Self::Heap(vec) => vec,
+            Self::Inline { .. } => unreachable!("storage was spilled"),
+        }
+    }
+
+    /// Append a byte
+    #[inline(always)]
+    pub(crate) fn push(&mut self, b: u8) {
+        match self {
+            Self::Inline { len, buf } if usize::from(*len) < INLINE_CAP => {
+                buf[usize::from(*len)] = b;
+                *len += 1;
+            }
+            Self::Inline { .. } => self.spill(1).push(b),
+            Self::Heap(vec) => vec.push(b),
+        }
+    }
+
+    /// Shorten to `new_len` bytes, has no effect if `new_len` is greater or equal to the
+    /// current len.
+    pub(crate) fn truncate(&mut self, new_len: usize) {
+        match self {
+            Self::Inline { len, .. } => {
+                if new_len < usize::from(*len) {
+                    // new_len is smaller than a u8 len
+                    *len = new_len as u8;
+                }
+            }
+            Self::Heap(vec) => vec.truncate(new_len),
+        }
+    }
+
+    /// Remove all bytes
+    pub(crate) fn clear(&mut self) {
+        self.truncate(0)
+    }
+
+    /// Reserve space for at least `additional` more bytes
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        match self {
+            Self::Inline { len, .. } if usize::from(*len) + additional > INLINE_CAP => {
+                self.spill(additional);
+            }
+            Self::Inline { .. } => (),
+            Self::Heap(vec) => vec.reserve(additional),
+        }
+    }
+
+    /// Append all bytes of `bytes`
+    pub(crate) fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.reserve(bytes.len());
+        match self {
+            Self::Inline { len, buf } => {
+                let start = usize::from(*len);
+                buf[start..start + bytes.len()].copy_from_slice(bytes);
+                // The bytes fit inline after the reserve
+                *len += bytes.len() as u8;
+            }
+            Self::Heap(vec) => vec.extend_from_slice(bytes),
+        }
+    }
+}
+
+impl Deref for Storage {
+    type Target = [u8];
+
+    #[inline(always)]
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Inline { len, buf } => &buf[..usize::from(*len)],
+            Self::Heap(vec) => vec,
+        }
+    }
+}
+
+impl DerefMut for Storage {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match self {
+            Self::Inline { len, buf } => &mut buf[..usize::from(*len)],
+            Self::Heap(vec) => vec,
+        }
+    }
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Clones of short heap storage are stored inline again.
+impl Clone for Storage {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Heap(vec) if vec.len() > INLINE_CAP => Self::Heap(vec.clone()),
+            _ => {
+                let mut me = Self::new();
+                me.extend_from_slice(self);
+                me
+            }
+        }
+    }
+}
+
+impl PartialEq for Storage {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for Storage {}
+
+impl PartialOrd for Storage {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Storage {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl Hash for Storage {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spill() {
+        assert!(core::mem::size_of::<Storage>() <= core::mem::size_of::<Vec<u8>>());
+
+        let mut storage = Storage::new();
+        let mut model = Vec::new();
+        for b in 0..INLINE_CAP as u8 {
+            storage.push(b);
+            model.push(b);
+        }
+        assert!(storage.is_inline());
+        assert_eq!(&*storage, &model[..]);
+        storage.push(0xff);
+        model.push(0xff);
+        assert!(!storage.is_inline());
+        assert_eq!(&*storage, &model[..]);
+
+        storage.truncate(INLINE_CAP);
+        model.pop();
+        assert!(storage.clone().is_inline());
+        assert!(storage == storage.clone());
+
+        let mut inline = Storage::new();
+        inline.extend_from_slice(&model[..4]);
+        inline.extend_from_slice(&model[4..]);
+        assert!(inline.is_inline());
+        assert!(inline == storage);
+        inline.extend_from_slice(&[1, 2]);
+        assert!(!inline.is_inline());
+        inline.truncate(3);
+        assert_eq!(inline.into_vec(), [0, 1, 2]);
+
+        let empty = Storage::with_capacity(INLINE_CAP + 1);
+        assert!(!empty.is_inline());
+        assert!(empty.is_empty());
+    }
+}
@@ -1,82 +1,64 @@
 use alloc::string::String;
 
-use crate::*;
+use crate::{
+    sink::{Push, PushRules},
+    *,
+};
 
 /// Write characters of cologne codes 
 pub fn utf8_to_cologne_phonetics_string(bytes: &[u8], outbuf: &mut String) {
-    let mut utf8 = false;
-    // All values are interpreted as a normal alphabetic character and this maps to their alphabet
-    // index, most ascii punctuation and whitespace characters are 26 and count as a stop
-    let mut last = [26, 26];
-    // Wether the previous character was uncertain and is not yet written
-    let mut prev_uncertain = false;
+    utf8_to_cologne_phonetics_string_with_options(bytes, outbuf, &EncodeOptions::DEFAULT)
+}
+
+/// Write characters of cologne codes using the given [`EncodeOptions`]
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{utf8_to_cologne_phonetics_string_with_options, EncodeOptions, PunctuationMode};
+/// let opts = EncodeOptions::new().hyphen(PunctuationMode::Join);
+/// let mut outbuf = String::new();
+/// utf8_to_cologne_phonetics_string_with_options("Müller-Lüdenscheidt".as_bytes(), &mut outbuf, &opts);
+/// assert_eq!(outbuf, "65752682");
+/// ```
+pub fn utf8_to_cologne_phonetics_string_with_options(
+    bytes: &[u8],
+    outbuf: &mut String,
+    opts: &EncodeOptions,
+) {
     let mut cologne_string = CologneString {
         inner: outbuf,
-        last: [None;2],
+        rules: PushRules::EMPTY,
     };
-    let outbuf = &mut cologne_string;
-
-    for b in bytes {
-        let b = *b;
-        iter!(b, utf8, last, prev_uncertain, cologne_code_push_char, outbuf);
-    }
-
-    match outbuf.last {
-        [Some(a), Some(CologneCode::Space)] => {
-            outbuf.inner.push(a.as_char());
-        }
-        [Some(a @ CologneCode::Space), Some(b @ CologneCode::Class0)] => {
-            outbuf.inner.push(a.as_char());
-            outbuf.inner.push(b.as_char());
-        }
-        [Some(a), Some(b)] => {
-            outbuf.inner.push(a.as_char());
-            if b != CologneCode::Class0 {
-                outbuf.inner.push(b.as_char());
-            }
-        }
-        [Some(CologneCode::Space), None] => (),
-        [Some(a), None] => {
-            outbuf.inner.push(a.as_char());
-        }
-        _ => ()
-    }
-}
-
-/// Push a char to the end of a `CologneString` wrapper
-fn cologne_code_push_char(outbuf: &mut CologneString, code: CologneCode) {
-    if !outbuf.last().is_some_and(|val| val == code) {
-        match &mut outbuf.last {
-            &mut [Some(a), Some(CologneCode::Class0)] if a != CologneCode::Space => {
-                outbuf.last[1] = Some(code);
-            }
-            &mut [Some(ref mut a), Some(ref mut b)] => {
-                outbuf.inner.push(a.as_char());
-                *a = *b;
-                *b = code;
-            },
-            &mut [Some(_), ref mut b @ None] => {
-                *b = Some(code);
-            }
-            &mut [ref mut a @ None, None] => {
-                *a = Some(code);
-            }
-            &mut [None, Some(_)] => unreachable!(),
-        }
-    }
+    encode_into(bytes, opts, &mut cologne_string);
 }
 
-/// Small wrapper structure to push to the string efficiently
+/// [`CologneSink`] writing chars to a `String`, the last two codes are only written once they
+/// can't change anymore
 #[derive(Debug)]
 struct CologneString<'a> {
-    last: [Option<CologneCode>;2],
+    /// Rules for the next code, holding the codes which are not yet written
+    rules: PushRules,
+    /// The output string
     inner: &'a mut String,
 }
 
-impl CologneString<'_> {
-    /// Get the last added `CologneCode`
-    fn last(&self) -> Option<CologneCode> {
-        self.last[1].or(self.last[0])
+impl CologneSink for CologneString<'_> {
+    fn push(&mut self, code: CologneCode) {
+        let [before_last, _] = self.rules.last();
+        if self.rules.push(code) == Push::Append {
+            if let Some(code) = before_last {
+                self.inner.push(code.as_char());
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        let removed = self.rules.finish();
+        let [before_last, last] = self.rules.last();
+        let last = last.filter(|_| removed.is_none());
+        for code in [before_last, last].into_iter().flatten() {
+            self.inner.push(code.as_char());
+        }
     }
 }
 
@@ -214,5 +196,48 @@ mod test {
         assert_eq!(outbuf_little, resstr);
         assert_eq!(outbuf_little, outbuf);
     }
-}
 
+    #[test]
+    fn punctuation_modes() {
+        let mut outbuf = String::new();
+        utf8_to_cologne_phonetics_string_with_options(
+            "Müller-Lüdenscheidt".as_bytes(),
+            &mut outbuf,
+            &EncodeOptions::new().hyphen(PunctuationMode::Join),
+        );
+        assert_eq!(outbuf, "65752682");
+
+        let mut outbuf = String::new();
+        utf8_to_cologne_phonetics_string_with_options(
+            b"D'Angelo O'Brien",
+            &mut outbuf,
+            &EncodeOptions::new().apostrophe(PunctuationMode::Ignore),
+        );
+        assert_eq!(outbuf, "2645 0176");
+    }
+
+    #[test]
+    fn split_camel_case() {
+        let mut outbuf = String::new();
+        utf8_to_cologne_phonetics_string_with_options(
+            b"MaxMustermann",
+            &mut outbuf,
+            &EncodeOptions::new().split_camel_case(true),
+        );
+        assert_eq!(outbuf, "648 682766");
+    }
+
+    #[test]
+    fn trailing_uncertain() {
+        let mut outbuf = String::new();
+        utf8_to_cologne_phonetics_string(b"Pat Bad Bac Dop", &mut outbuf);
+        assert_eq!(outbuf, "12 12 18 21");
+    }
+
+    #[test]
+    fn leading_break() {
+        let mut outbuf = String::new();
+        utf8_to_cologne_phonetics_string(b" - h Aro", &mut outbuf);
+        assert_eq!(outbuf, "07");
+    }
+}
@@ -282,3 +282,86 @@ fn alphabet() {
     assert_eq!(outbuf_little, resvec);
     assert_eq!(outbuf_little, outbuf);
 }
+
+#[test]
+fn trailing_uncertain() {
+    let mut outbuf = Vec::new();
+    utf8_to_cologne_phonetics_vec(b"Pat", &mut outbuf);
+    assert_eq!(outbuf, &[CologneCode::Class1, CologneCode::Class2]);
+    outbuf.clear();
+    utf8_to_cologne_phonetics_vec(b"Vogt", &mut outbuf);
+    assert_eq!(
+        outbuf,
+        &[CologneCode::Class3, CologneCode::Class4, CologneCode::Class2]
+    );
+}
+
+#[test]
+fn leading_break() {
+    let mut outbuf = Vec::new();
+    utf8_to_cologne_phonetics_vec(b" - h a", &mut outbuf);
+    assert_eq!(outbuf, &[CologneCode::Class0]);
+}
+
+#[test]
+fn code_conversions() {
+    for nibble in 0..=u8::MAX {
+        match CologneCode::try_from(nibble) {
+            Ok(code) => assert_eq!(code.get(), nibble),
+            Err(e) => {
+                assert!(nibble > 8 && nibble != CologneCode::Space.get());
+                assert_eq!(e, InvalidCode::Nibble { index: 0, value: nibble });
+            }
+        }
+    }
+    for c in ['0', '1', '2', '3', '4', '5', '6', '7', '8', ' '] {
+        assert_eq!(CologneCode::try_from(c).map(CologneCode::as_char), Ok(c));
+    }
+    for c in ['9', 'a', '-', '\0', 'ä'] {
+        assert_eq!(CologneCode::try_from(c), Err(InvalidCode::Char { index: 0, value: c }));
+    }
+}
+
+/// Inputs covering every rule and word break of the encoding, shared by the tests which compare
+/// an encoder with [`CologneVec`]
+pub(crate) const INPUTS: [&str; 28] = [
+    "",
+    "   ",
+    "a",
+    "h",
+    " a ",
+    "Pat",
+    "Anna",
+    "Wikipedia",
+    "Chemnitz",
+    "Müller-Lüdenscheidt",
+    "--- Müller-Lüdenscheidt ---",
+    "Er kam, Er sah, Er siegte",
+    "Anhand von Grundlagen",
+    "Hacico Chrysler Zacharias",
+    "aho aho aho",
+    "  aho   aho aho  ",
+    "  Wikipedia's  ",
+    "Hans , Peter",
+    "  Xaver Philipp  ",
+    "Xaver xXxX MaxMustermann",
+    "MaxMustermann GrüßGott",
+    "Café au lait",
+    "Blöd Buße",
+    "aeiou ouie bab abba ohoho",
+    "A B C D E F G H I J K L M N O P Q R S T U V W X Y Z",
+    "Ax Bx Cx Dx Ex Fx Gx Hx Ix Jx Kx Lx Mx Nx Ox Px Qx Rx Sx Tx Ux Vx Wx Xx Yx Zx",
+    "xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx wikipedia",
+    "xXxXxXxXxXxXxXxXxXxXxXxXxXxXxXxXxXxXxXxX MaxMustermann",
+];
+
+/// The option sets every encoder is compared with on [`INPUTS`]
+pub(crate) fn option_sets() -> [EncodeOptions; 3] {
+    [
+        EncodeOptions::DEFAULT,
+        EncodeOptions::new().split_camel_case(true),
+        EncodeOptions::new()
+            .hyphen(PunctuationMode::Join)
+            .apostrophe(PunctuationMode::Ignore),
+    ]
+}
@@ -0,0 +1,199 @@
+//! Encoding into caller provided byte slices.
+
+use core::fmt;
+
+use crate::{
+    sink::{CodeBuffer, IndexSink},
+    *,
+};
+
+/// Error returned when the codes don't fit into the output slice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BufferTooSmall {
+    /// The number of bytes the output slice needs to hold all codes
+    pub required: usize,
+}
+
+impl fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "output buffer too small, {} bytes required", self.required)
+    }
+}
+
+impl core::error::Error for BufferTooSmall {}
+
+/// Get the maximum number of codes an input of `input_len` bytes can be encoded to with the
+/// default [`EncodeOptions`].
+///
+/// This is the number of bytes [`encode_to_slice`] writes at most, [`encode_to_slice_packed`]
+/// writes at most half of it rounded up. The bound is tight: every letter is encoded to at most
+/// one code, except `X` which can be encoded to the two codes `48` (e.g. `"xxx"` is encoded to
+/// `"484848"`).
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{encode_to_slice, max_encoded_len};
+/// let input = b"Xaver Xylophon";
+/// let mut out = [0; max_encoded_len(14)];
+/// let len = encode_to_slice(input, &mut out).unwrap();
+/// assert_eq!(&out[..len], b"4837 48536");
+/// ```
+pub const fn max_encoded_len(input_len: usize) -> usize {
+    EncodeOptions::DEFAULT.max_encoded_len(input_len)
+}
+
+/// Encode the given utf8 bytes as ASCII digits and spaces into `out`, like the [`Display`]
+/// implementation of [`CologneSlice`]. Returns the number of written bytes.
+///
+/// Never allocates, if `out` is too small the returned [`BufferTooSmall`] contains the required
+/// size and the contents of `out` are unspecified. [`max_encoded_len`] gives a size that is
+/// always sufficient.
+///
+/// [`Display`]: core::fmt::Display
+pub fn encode_to_slice(input: &[u8], out: &mut [u8]) -> Result<usize, BufferTooSmall> {
+    encode_to_slice_with_options(input, out, &EncodeOptions::DEFAULT)
+}
+
+/// [`encode_to_slice`] using the given [`EncodeOptions`].
+pub fn encode_to_slice_with_options(
+    input: &[u8],
+    out: &mut [u8],
+    opts: &EncodeOptions,
+) -> Result<usize, BufferTooSmall> {
+    let mut sink = IndexSink::new(SliceBuffer { out, packed: false });
+    encode_into(input, opts, &mut sink);
+    if sink.len > sink.out.out.len() {
+        Err(BufferTooSmall { required: sink.len })
+    } else {
+        Ok(sink.len)
+    }
+}
+
+/// Encode the given utf8 bytes as packed nibbles into `out`, like the raw storage of a
+/// [`CologneVec`]. Returns the number of codes, `len.div_ceil(2)` bytes are written.
+///
+/// Never allocates, if `out` is too small the returned [`BufferTooSmall`] contains the required
+/// size and the contents of `out` are unspecified.
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{encode_to_slice_packed, BufferTooSmall};
+/// let mut out = [0; 8];
+/// assert_eq!(encode_to_slice_packed("Müller-Lüdenscheidt".as_bytes(), &mut out), Ok(9));
+/// assert_eq!(out[..5], [0x65, 0x7e, 0x52, 0x68, 0x20]);
+/// assert_eq!(
+///     encode_to_slice_packed("Müller-Lüdenscheidt".as_bytes(), &mut out[..4]),
+///     Err(BufferTooSmall { required: 5 })
+/// );
+/// ```
+pub fn encode_to_slice_packed(input: &[u8], out: &mut [u8]) -> Result<usize, BufferTooSmall> {
+    encode_to_slice_packed_with_options(input, out, &EncodeOptions::DEFAULT)
+}
+
+/// [`encode_to_slice_packed`] using the given [`EncodeOptions`].
+pub fn encode_to_slice_packed_with_options(
+    input: &[u8],
+    out: &mut [u8],
+    opts: &EncodeOptions,
+) -> Result<usize, BufferTooSmall> {
+    let mut sink = IndexSink::new(SliceBuffer { out, packed: true });
+    encode_into(input, opts, &mut sink);
+    let required = sink.len.div_ceil(2);
+    if required > sink.out.out.len() {
+        Err(BufferTooSmall { required })
+    } else {
+        Ok(sink.len)
+    }
+}
+
+/// Write `code` at the code index `idx` of `out` as ASCII or packed, if it fits
+pub(crate) fn write_code(out: &mut [u8], packed: bool, idx: usize, code: CologneCode) {
+    if packed {
+        if let Some(b) = out.get_mut(idx >> 1) {
+            if idx & 0x01 == 0 {
+                // The low nibble is written next or stays as zero padding
+                *b = code.get() << 4;
+            } else {
+                *b = (*b & 0xf0) | code.get();
+            }
+        }
+    } else if let Some(b) = out.get_mut(idx) {
+        *b = code.as_char() as u8;
+    }
+}
+
+/// [`CodeBuffer`] writing ASCII or packed codes into a slice. Codes which don't fit are still
+/// counted by the [`IndexSink`] to report the required size.
+struct SliceBuffer<'a> {
+    /// The output slice
+    out: &'a mut [u8],
+    /// Wether the codes are packed into nibbles instead of ASCII
+    packed: bool,
+}
+
+impl CodeBuffer for SliceBuffer<'_> {
+    fn write(&mut self, idx: usize, code: CologneCode) {
+        write_code(self.out, self.packed, idx, code)
+    }
+
+    fn remove(&mut self, idx: usize) {
+        // Reset the nibble to the zero padding
+        if self.packed && idx & 0x01 == 1 {
+            self.write(idx, CologneCode::Class0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "alloc")]
+    use crate::tests::{option_sets, INPUTS};
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn same_as_array() {
+        for opts in option_sets() {
+            for input in INPUTS {
+                let mut arr = CologneArray::<64>::new();
+                arr.read_from_utf8_with_options(input.as_bytes(), &opts).unwrap();
+
+                let mut out = [0xff; 128];
+                let len = encode_to_slice_with_options(input.as_bytes(), &mut out, &opts);
+                assert_eq!(len, Ok(arr.len()), "{:?}", input);
+                let ascii = arr.iter().map(|c| c.as_char() as u8);
+                assert!(out[..arr.len()].iter().copied().eq(ascii));
+                assert!(len.unwrap() <= opts.max_encoded_len(input.len()));
+
+                let mut out = [0xff; 64];
+                let len = encode_to_slice_packed_with_options(input.as_bytes(), &mut out, &opts);
+                assert_eq!(len, Ok(arr.len()), "{:?}", input);
+                assert_eq!(&out[..arr.get_raw().len()], arr.get_raw());
+
+                for size in 0..arr.len() {
+                    let mut out = [0; 128];
+                    assert_eq!(
+                        encode_to_slice_with_options(input.as_bytes(), &mut out[..size], &opts),
+                        Err(BufferTooSmall { required: arr.len() })
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bound_is_tight() {
+        let mut out = [0; 64];
+        for n in 0..16 {
+            let input = [b'x'; 16];
+            assert_eq!(encode_to_slice(&input[..n], &mut out), Ok(max_encoded_len(n)));
+
+            let input = *b"xXxXxXxXxXxXxXxX";
+            let opts = EncodeOptions::new().split_camel_case(true);
+            assert_eq!(
+                encode_to_slice_with_options(&input[..n], &mut out, &opts),
+                Ok(opts.max_encoded_len(n))
+            );
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Variant of the cologne phonetics which keeps the positions of vowels.
+
+use core::fmt;
+
+use crate::*;
+
+/// [`CologneCode`]s of the vowel retaining variant of the cologne phonetics.
+///
+/// The standard encoding removes every `0` which is not at the start of a word, so all names
+/// with the same consonants collide. This variant keeps every run of vowels between consonant
+/// codes as a single `0`, it only applies the first two stages of the encoding
+/// ([`encode_raw`] and [`collapse`]) and skips [`remove_zeros`].
+///
+/// `VowelCodes` is a distinct type which doesn't compare with or convert into a [`CologneVec`],
+/// so codes of the two encodings can't be mixed by accident. The wrapped codes are available
+/// through [`as_slice`](Self::as_slice) and [`into_codes`](Self::into_codes).
+///
+/// # Example
+/// ```
+/// # use cologne_phonetics::{CologneVec, VowelCodes};
+/// let ott = VowelCodes::encode(b"Ott");
+/// let otto = VowelCodes::encode(b"Otto");
+/// assert_eq!(ott.to_string(), "02");
+/// assert_eq!(otto.to_string(), "020");
+/// assert_ne!(ott, otto);
+///
+/// let mut standard = CologneVec::new();
+/// standard.read_from_utf8(b"Otto");
+/// assert_eq!(standard.to_string(), "02");
+/// assert_eq!(VowelCodes::encode(b"Maier"), VowelCodes::encode(b"Mayr"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VowelCodes(CologneVec);
+
+impl VowelCodes {
+    /// Encode the given utf8 bytes with the vowel retaining variant.
+    pub fn encode(bytes: &[u8]) -> Self {
+        Self::encode_with_options(bytes, &EncodeOptions::DEFAULT)
+    }
+
+    /// Encode the given utf8 bytes with the vowel retaining variant using the given
+    /// [`EncodeOptions`].
+    pub fn encode_with_options(bytes: &[u8], opts: &EncodeOptions) -> Self {
+        let mut sink = VowelSink(CologneVec::new());
+        encode_into(bytes, opts, &mut sink);
+        Self(sink.0)
+    }
+
+    /// Get the number of codes
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check if there are no codes
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Get a view of the codes
+    pub fn as_slice(&self) -> CologneSlice<'_> {
+        self.0.as_slice()
+    }
+
+    /// Unwrap the codes
+    pub fn into_codes(self) -> CologneVec {
+        self.0
+    }
+}
+
+impl fmt::Display for VowelCodes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// [`CologneSink`] collapsing repeated codes but keeping all zeros
+struct VowelSink(CologneVec);
+
+impl CologneSink for VowelSink {
+    fn push(&mut self, code: CologneCode) {
+        if code == CologneCode::Space && self.0.is_empty() {
+            return;
+        }
+        if self.0.last() != Some(code) {
+            self.0.push_raw(code);
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.0.last() == Some(CologneCode::Space) {
+            self.0.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::tests::{option_sets, INPUTS};
+
+    #[test]
+    fn keeps_vowels() {
+        let encode = |input: &str| VowelCodes::encode(input.as_bytes()).to_string();
+        assert_eq!(encode(""), "");
+        assert_eq!(encode("h"), "");
+        assert_eq!(encode("Anna"), "060");
+        assert_eq!(encode("Ann"), "06");
+        assert_eq!(encode("Wikipedia"), "30401020");
+        assert_eq!(encode("  Er kam, Er sah  "), "07 406 07 80");
+        assert_eq!(encode("Maier"), encode("Mauer"));
+        assert_ne!(encode("Meier"), encode("Meiere"));
+    }
+
+    #[test]
+    fn stages() {
+        for opts in option_sets() {
+            for input in INPUTS {
+                let vowels = VowelCodes::encode_with_options(input.as_bytes(), &opts);
+                let raw = encode_raw_with_options(input.as_bytes(), &opts);
+                let collapsed = collapse(raw.as_slice());
+                assert_eq!(vowels.as_slice(), collapsed.as_slice(), "{:?}", input);
+
+                let mut vec = CologneVec::new();
+                vec.read_from_utf8_with_options(input.as_bytes(), &opts);
+                assert_eq!(remove_zeros(vowels.as_slice()), vec, "{:?}", input);
+            }
+        }
+    }
+}
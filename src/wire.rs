@@ -0,0 +1,276 @@
+//! Stable, versioned binary format for [`CologneCode`]s, see [`WIRE_FORMAT_VERSION`].
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::{slice::validate_packed, *};
+
+/// The version byte written in front of every encoded sequence of codes in the stable, versioned
+/// binary format for [`CologneCode`]s.
+///
+/// Unlike [`CologneVec::into_raw`] this format is documented and will not change with the
+/// internal layout of [`CologneVec`]. An encoded sequence of codes consists of:
+///
+/// 1. A single version byte, currently `1`.
+/// 2. The number of codes as unsigned LEB128 varint of at most 10 bytes. The shortest encoding
+///    is required, the last byte of a multi byte varint must not be `0`.
+/// 3. The codes packed two per byte, the first code of a byte in the high nibble. Every code is
+///    stored as its [`CologneCode::get`] value, the padding nibble of an odd number of codes
+///    must be `0`.
+///
+/// Multiple encoded sequences can be written directly after each other, the decoding functions
+/// return the remaining bytes.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// # use cologne_phonetics::{CologneVec, CologneSlice};
+/// let mut buf = CologneVec::new();
+/// buf.read_from_utf8("Müller-Lüdenscheidt".as_bytes());
+/// let bytes = buf.to_bytes();
+/// assert_eq!(bytes, [1, 9, 0x65, 0x7e, 0x52, 0x68, 0x20]);
+///
+/// let (slice, rest) = CologneSlice::from_bytes(&bytes).unwrap();
+/// assert_eq!(slice, buf);
+/// assert!(rest.is_empty());
+/// # }
+/// ```
+pub const WIRE_FORMAT_VERSION: u8 = 1;
+
+/// Maximum number of bytes of the varint length
+const MAX_VARINT_LEN: usize = 10;
+
+/// Error returned when decoding the binary format fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WireError {
+    /// The version byte is not supported by this version of the crate.
+    UnsupportedVersion(u8),
+    /// The input ended before the complete sequence was read.
+    UnexpectedEnd,
+    /// The varint length is longer than 10 bytes or does not fit into a `usize`.
+    LengthOverflow,
+    /// The varint length is not encoded in its shortest form.
+    OverlongLength,
+    /// The packed codes are invalid.
+    InvalidCode(InvalidCode),
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion(v) => write!(f, "unsupported wire format version {}", v),
+            Self::UnexpectedEnd => write!(f, "unexpected end of cologne code bytes"),
+            Self::LengthOverflow => write!(f, "cologne code length overflows"),
+            Self::OverlongLength => write!(f, "cologne code length is not in its shortest form"),
+            Self::InvalidCode(e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+impl core::error::Error for WireError {}
+
+impl From<InvalidCode> for WireError {
+    fn from(e: InvalidCode) -> Self {
+        Self::InvalidCode(e)
+    }
+}
+
+/// Write the header consisting of the version and varint `len` into `out`, returns the number
+/// of written bytes.
+fn write_header(len: usize, out: &mut [u8; MAX_VARINT_LEN + 1]) -> usize {
+    out[0] = WIRE_FORMAT_VERSION;
+    let mut val = len as u64;
+    let mut i = 1;
+    loop {
+        let b = (val & 0x7f) as u8;
+        val >>= 7;
+        if val == 0 {
+            out[i] = b;
+            return i + 1;
+        }
+        out[i] = b | 0x80;
+        i += 1;
+    }
+}
+
+/// Read the header from `bytes`, returns the number of codes and the remaining bytes.
+fn read_header(bytes: &[u8]) -> Result<(usize, &[u8]), WireError> {
+    let (&version, mut rest) = bytes.split_first().ok_or(WireError::UnexpectedEnd)?;
+    if version != WIRE_FORMAT_VERSION {
+        return Err(WireError::UnsupportedVersion(version));
+    }
+    let mut len = 0u64;
+    for i in 0..MAX_VARINT_LEN {
+        let (&b, r) = rest.split_first().ok_or(WireError::UnexpectedEnd)?;
+        rest = r;
+        let bits = u64::from(b & 0x7f);
+        let shift = 7 * i as u32;
+        if shift == 63 && bits > 1 {
+            return Err(WireError::LengthOverflow);
+        }
+        len |= bits << shift;
+        if b & 0x80 == 0 {
+            // Only a length of one byte may end with a zero byte
+            if i > 0 && b == 0 {
+                return Err(WireError::OverlongLength);
+            }
+            let len = usize::try_from(len).map_err(|_| WireError::LengthOverflow)?;
+            return Ok((len, rest));
+        }
+    }
+    Err(WireError::LengthOverflow)
+}
+
+impl<'a> CologneSlice<'a> {
+    /// Get the number of bytes [`to_bytes`](Self::to_bytes) writes for this slice.
+    pub fn encoded_len(&self) -> usize {
+        let mut header = [0; MAX_VARINT_LEN + 1];
+        write_header(self.len(), &mut header) + self.len().div_ceil(2)
+    }
+
+    /// Encode this slice in the [binary format](crate::WIRE_FORMAT_VERSION) and append it to `out`.
+    #[cfg(feature = "alloc")]
+    pub fn write_bytes(&self, out: &mut Vec<u8>) {
+        let mut header = [0; MAX_VARINT_LEN + 1];
+        let header_len = write_header(self.len(), &mut header);
+        out.extend_from_slice(&header[..header_len]);
+        out.extend(self.packed_iter());
+    }
+
+    /// Encode this slice in the [binary format](crate::WIRE_FORMAT_VERSION).
+    #[cfg(feature = "alloc")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.encoded_len());
+        self.write_bytes(&mut out);
+        out
+    }
+
+    /// Encode this slice in the [binary format](crate::WIRE_FORMAT_VERSION) into the writer `w`.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: std::io::Write + ?Sized>(&self, w: &mut W) -> std::io::Result<()> {
+        let mut header = [0; MAX_VARINT_LEN + 1];
+        let header_len = write_header(self.len(), &mut header);
+        w.write_all(&header[..header_len])?;
+        let mut buf = [0u8; 64];
+        let mut filled = 0;
+        for b in self.packed_iter() {
+            buf[filled] = b;
+            filled += 1;
+            if filled == buf.len() {
+                w.write_all(&buf)?;
+                filled = 0;
+            }
+        }
+        w.write_all(&buf[..filled])
+    }
+
+    /// Decode a slice in the [binary format](crate::WIRE_FORMAT_VERSION) from the start of `bytes` without
+    /// copying the codes. Returns the slice and the remaining bytes after it.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<(CologneSlice<'a>, &'a [u8]), WireError> {
+        let (len, rest) = read_header(bytes)?;
+        let byte_len = len.div_ceil(2);
+        if rest.len() < byte_len {
+            return Err(WireError::UnexpectedEnd);
+        }
+        let (packed, rest) = rest.split_at(byte_len);
+        validate_packed(packed, len)?;
+        // SAFETY: All len nibbles were validated
+        Ok((unsafe { CologneSlice::from_raw_parts(packed, 0, len) }, rest))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl CologneVec {
+    /// Encode this `CologneVec` in the [binary format](crate::WIRE_FORMAT_VERSION).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.as_slice().to_bytes()
+    }
+
+    /// Encode this `CologneVec` in the [binary format](crate::WIRE_FORMAT_VERSION) into the writer `w`.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: std::io::Write + ?Sized>(&self, w: &mut W) -> std::io::Result<()> {
+        self.as_slice().write_to(w)
+    }
+
+    /// Decode a `CologneVec` in the [binary format](crate::WIRE_FORMAT_VERSION) from the start of `bytes`.
+    /// Returns the `CologneVec` and the remaining bytes after it.
+    pub fn from_bytes(bytes: &[u8]) -> Result<(CologneVec, &[u8]), WireError> {
+        let (slice, rest) = CologneSlice::from_bytes(bytes)?;
+        Ok((slice.to_vec(), rest))
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn round_trip() {
+        let mut out = Vec::new();
+        let inputs = ["", "a", "Wikipedia", "Er kam, Er sah, Er siegte", "aho aho aho"];
+        let mut vecs = Vec::new();
+        for input in inputs {
+            let mut vec = CologneVec::new();
+            vec.read_from_utf8(input.as_bytes());
+            assert_eq!(vec.to_bytes().len(), vec.as_slice().encoded_len());
+            vec.write_to(&mut out).unwrap();
+            vecs.push(vec);
+        }
+        let mut rest = &out[..];
+        for vec in &vecs {
+            let (decoded, r) = CologneVec::from_bytes(rest).unwrap();
+            assert_eq!(&decoded, vec);
+            rest = r;
+        }
+        assert!(rest.is_empty());
+
+        // Odd aligned slices are written with the same layout
+        let vec = &vecs[3];
+        let sub = vec.slice(1..);
+        let bytes = sub.to_bytes();
+        let (decoded, _) = CologneSlice::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, sub);
+    }
+
+    #[test]
+    fn varint() {
+        for len in [0, 1, 127, 128, 300, 16_383, 16_384] {
+            let vec: CologneVec = core::iter::repeat_n(CologneCode::Class4, len).collect();
+            let bytes = vec.to_bytes();
+            let header_len = bytes.len() - len.div_ceil(2);
+            assert_eq!(header_len, 1 + (len.max(1).ilog2() as usize / 7) + 1);
+            assert_eq!(CologneVec::from_bytes(&bytes).unwrap().0, vec);
+        }
+        assert_eq!(CologneVec::from_bytes(&[1, 0x80, 0x01]), Err(WireError::UnexpectedEnd));
+        let long = [1, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        assert_eq!(CologneVec::from_bytes(&long), Err(WireError::LengthOverflow));
+    }
+
+    #[test]
+    fn overlong_varint() {
+        assert_eq!(CologneVec::from_bytes(&[1, 0x80, 0x00]), Err(WireError::OverlongLength));
+        assert_eq!(
+            CologneVec::from_bytes(&[1, 0x81, 0x80, 0x00, 0x12]),
+            Err(WireError::OverlongLength)
+        );
+        assert_eq!(CologneVec::from_bytes(&[1, 0x00]).unwrap().0, CologneVec::new());
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(CologneVec::from_bytes(&[]), Err(WireError::UnexpectedEnd));
+        assert_eq!(CologneVec::from_bytes(&[2, 0]), Err(WireError::UnsupportedVersion(2)));
+        assert_eq!(CologneVec::from_bytes(&[1, 3, 0x12]), Err(WireError::UnexpectedEnd));
+        assert_eq!(
+            CologneSlice::from_bytes(&[1, 3, 0x12, 0x31]),
+            Err(WireError::InvalidCode(InvalidCode::Padding { value: 1 }))
+        );
+        assert_eq!(
+            CologneSlice::from_bytes(&[1, 2, 0x1a]),
+            Err(WireError::InvalidCode(InvalidCode::Nibble { index: 1, value: 0xa }))
+        );
+    }
+}